@@ -0,0 +1,293 @@
+// `live-wgsl bench`: render a shader or project offscreen as fast as the gpu goes and report
+// statistics of the gpu time per frame, to compare versions of a shader.
+// every frame advances the clocks by 1/60 s, so runs render the same frames. the gpu is waited for
+// after every frame, and warm-up frames, like the first ones after pipeline creation, are discarded.
+// the gpu time comes from timestamp queries, or without them from waiting for the gpu.
+
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::diagnostics;
+use crate::features;
+use crate::gpu::adapter::{self, AdapterSettings};
+use crate::gpu::gpu_timer::GpuTimer;
+use crate::gpu::headless::Headless;
+use crate::gpu::renderer::ViewportInfo;
+use crate::gpu::Renderer;
+use crate::project::Project;
+
+// like a surface, the shader writes linear colors
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const TIME_STEP: f32 = 1.0 / 60.0;
+
+pub struct BenchSettings {
+    pub size: [u32; 2],
+    pub frames: u32,
+    pub warm_up: u32,
+    pub json: bool,
+    pub adapter: AdapterSettings,
+}
+
+// milliseconds
+#[derive(Serialize)]
+struct Statistics {
+    min: f64,
+    median: f64,
+    p95: f64,
+    max: f64,
+    mean: f64,
+}
+
+#[derive(Serialize)]
+struct BenchResult<'a> {
+    name: &'a str,
+    adapter: String,
+    backend: String,
+    // `Cpu` for software rendering
+    device_type: String,
+    width: u32,
+    height: u32,
+    // 1 without msaa
+    sample_count: u32,
+    frames: u32,
+    warm_up: u32,
+    // `timestamps` or `waited`
+    gpu_time_source: &'a str,
+    gpu_time: Statistics,
+    frames_per_second: f64,
+    megapixels_per_second: f64,
+}
+
+// for `--size`, like `1920x1080`
+pub fn parse_size(size: &str) -> Result<[u32; 2], String> {
+    let (width, height) = size
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, got `{}`", size))?;
+    let parse = |value: &str| match value.trim().parse::<u32>() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(format!("invalid size `{}`", size)),
+    };
+    Ok([parse(width)?, parse(height)?])
+}
+
+pub fn run(path: &str, settings: &BenchSettings) -> ExitCode {
+    match bench(Path::new(path), settings) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            diagnostics::emit_error(Path::new(path), &e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn bench(path: &Path, settings: &BenchSettings) -> Result<(), String> {
+    if settings.frames == 0 {
+        return Err("There are no frames to measure, `--frames` must be at least 1".to_string());
+    }
+
+    let project = if Project::is_project_path(path) {
+        Project::load(path)?
+    } else {
+        Project::single(path)
+    };
+    let sources = project
+        .files()
+        .iter()
+        .map(|file| {
+            std::fs::read_to_string(file)
+                .map_err(|e| format!("Error when reading {}: {}", file.display(), e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let shader_sources = project
+        .shader_sources(&sources, None)
+        .map_err(|(index, e)| {
+            e.emit_to_stderr(&sources[index], &project.pass_path(index).to_string_lossy());
+            "The shader has errors".to_string()
+        })?;
+
+    let gpu = pollster::block_on(Headless::new(&settings.adapter))?;
+    features::check_passes(&shader_sources, gpu.device.features()).map_err(|(_, e)| e)?;
+    let max_size = gpu.device.limits().max_texture_dimension_2d;
+    if settings.size[0] > max_size || settings.size[1] > max_size {
+        return Err(format!(
+            "The size is larger than the GPU supports, {} pixels at most",
+            max_size
+        ));
+    }
+
+    let sample_count = adapter::sample_count(&gpu.adapter, TARGET_FORMAT);
+    let mut renderer = Renderer::new(
+        &gpu.device,
+        &gpu.queue,
+        TARGET_FORMAT,
+        sample_count,
+        include_str!("./fragment_default.wgsl"),
+    );
+    let passes = project.pass_descriptors(&gpu.device, &gpu.queue, shader_sources);
+    pollster::block_on(renderer.update_fragment(passes, &gpu.device))
+        .map_err(|(_, e)| e.to_string())?;
+
+    let target = create_target(&gpu.device, settings.size, 1);
+    let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let multi_sample_target = create_target(&gpu.device, settings.size, sample_count);
+    let multi_sample_view =
+        multi_sample_target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut gpu_timer = GpuTimer::new(&gpu.device, &gpu.queue);
+    if gpu_timer.is_none() {
+        eprintln!(
+            "The GPU does not support timestamp queries, the GPU time is measured by waiting for the GPU"
+        );
+    }
+
+    let viewport_info = |frame: u32| ViewportInfo {
+        size: [settings.size[0] as f32, settings.size[1] as f32],
+        time_from_start_up: frame as f32 * TIME_STEP,
+        time_from_update: frame as f32 * TIME_STEP,
+        mouse: [0.0, 0.0, -1.0, -1.0],
+        frame,
+        time_delta: TIME_STEP,
+        _padding: [0; 2],
+    };
+
+    // the first frame catches errors of the passes, and is warm-up
+    pollster::block_on(renderer.try_render(
+        &gpu.device,
+        &gpu.queue,
+        &target_view,
+        &multi_sample_view,
+        viewport_info(0),
+    ))
+    .map_err(|e| e.to_string())?;
+
+    let mut gpu_times = Vec::with_capacity(settings.frames as usize);
+    let mut measured = Duration::ZERO;
+    for frame in 1..=settings.warm_up + settings.frames {
+        let start = Instant::now();
+        let encoder = renderer.render(
+            &gpu.device,
+            &gpu.queue,
+            &target_view,
+            &multi_sample_view,
+            viewport_info(frame),
+            gpu_timer.as_mut(),
+        );
+        gpu.queue.submit(std::iter::once(encoder.finish()));
+        if let Some(gpu_timer) = gpu_timer.as_mut() {
+            gpu_timer.submitted();
+        }
+        gpu.device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|e| e.to_string())?;
+        let elapsed = start.elapsed();
+
+        if frame <= settings.warm_up {
+            // readbacks of warm-up frames are not measured
+            if let Some(gpu_timer) = gpu_timer.as_mut() {
+                gpu_timer.take_pass_times();
+            }
+            continue;
+        }
+        measured += elapsed;
+        let gpu_time = match gpu_timer.as_mut() {
+            Some(gpu_timer) => gpu_timer
+                .take_pass_times()
+                .map(|pass_times| pass_times.iter().sum::<f64>()),
+            None => Some(elapsed.as_secs_f64() * 1000.0),
+        };
+        gpu_times.extend(gpu_time);
+    }
+
+    if gpu_times.is_empty() {
+        return Err("No GPU time was read back".to_string());
+    }
+    gpu_times.sort_by(f64::total_cmp);
+
+    let frames_per_second = settings.frames as f64 / measured.as_secs_f64().max(f64::EPSILON);
+    let adapter = gpu.adapter.get_info();
+    let result = BenchResult {
+        name: &project.manifest.name,
+        adapter: adapter.name,
+        backend: adapter.backend.to_string(),
+        device_type: format!("{:?}", adapter.device_type),
+        width: settings.size[0],
+        height: settings.size[1],
+        sample_count,
+        frames: settings.frames,
+        warm_up: settings.warm_up,
+        gpu_time_source: if gpu_timer.is_some() {
+            "timestamps"
+        } else {
+            "waited"
+        },
+        gpu_time: Statistics {
+            min: gpu_times[0],
+            median: percentile(&gpu_times, 50.0),
+            p95: percentile(&gpu_times, 95.0),
+            max: gpu_times[gpu_times.len() - 1],
+            mean: gpu_times.iter().sum::<f64>() / gpu_times.len() as f64,
+        },
+        frames_per_second,
+        megapixels_per_second: frames_per_second
+            * settings.size[0] as f64
+            * settings.size[1] as f64
+            / 1_000_000.0,
+    };
+
+    if settings.json {
+        println!("{}", serde_json::to_string(&result).unwrap());
+    } else {
+        print_result(&result);
+    }
+    Ok(())
+}
+
+fn create_target(device: &wgpu::Device, size: [u32; 2], sample_count: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Bench Target"),
+        size: wgpu::Extent3d {
+            width: size[0],
+            height: size[1],
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format: TARGET_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    })
+}
+
+// nearest rank of sorted samples
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn print_result(result: &BenchResult) {
+    println!("Benchmark: {}", result.name);
+    println!(
+        "Adapter:   {} ({}, {})",
+        result.adapter, result.backend, result.device_type
+    );
+    println!(
+        "Size:      {}x{}, {}x MSAA, {} frames after {} warm-up frames",
+        result.width, result.height, result.sample_count, result.frames, result.warm_up
+    );
+    println!();
+    println!("GPU time ({}):", result.gpu_time_source);
+    println!("  min    {:>10.3} ms", result.gpu_time.min);
+    println!("  median {:>10.3} ms", result.gpu_time.median);
+    println!("  p95    {:>10.3} ms", result.gpu_time.p95);
+    println!("  max    {:>10.3} ms", result.gpu_time.max);
+    println!("  mean   {:>10.3} ms", result.gpu_time.mean);
+    println!();
+    println!(
+        "Throughput: {:.1} frames/s, {:.1} Mpixels/s",
+        result.frames_per_second, result.megapixels_per_second
+    );
+}
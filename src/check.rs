@@ -0,0 +1,274 @@
+// `live-wgsl check`: validate shaders and projects without a gpu, for ci.
+// every pass is parsed and validated as on a reload, then its interface is checked against what
+// the renderer binds: `fs_main` writing `@location(0)`, `Info` at binding 0 and the channels.
+// lints are printed as warnings, which do not fail the check.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use naga::{AddressSpace, Binding, ImageClass, ImageDimension, ScalarKind, ShaderStage, TypeInner};
+
+use crate::diagnostics::{self, CompileResult, Diagnostic, Severity};
+use crate::gpu::renderer::{ViewportInfo, CHANNEL_COUNT};
+use crate::lint::Lint;
+use crate::project::Project;
+use crate::shader::FRAGMENT_ENTRY_POINT;
+use crate::shadertoy::InfoMember;
+
+// the size of the `Info` uniform buffer
+const INFO_SIZE: u32 = std::mem::size_of::<ViewportInfo>() as u32;
+
+pub fn run(paths: &[String], json: bool) -> ExitCode {
+    let mut failed = 0;
+    for path in paths {
+        let (files, diagnostics) = check(Path::new(path));
+        let ok = diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity != Severity::Error);
+        if !ok {
+            failed += 1;
+        }
+
+        if json {
+            let result = CompileResult {
+                ok,
+                files: &files,
+                diagnostics: &diagnostics,
+            };
+            println!("{}", serde_json::to_string(&result).unwrap());
+        }
+    }
+
+    eprintln!("Checked {} shaders, {} failed", paths.len(), failed);
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// the files of a shader or project, and their problems
+fn check(path: &Path) -> (Vec<String>, Vec<Diagnostic>) {
+    let project = if Project::is_project_path(path) {
+        match Project::load(path) {
+            Ok(project) => project,
+            Err(e) => {
+                return (
+                    vec![path.to_string_lossy().into_owned()],
+                    vec![diagnostics::emit_error(path, &e)],
+                )
+            }
+        }
+    } else {
+        Project::single(path)
+    };
+
+    let files = project.files();
+    let file_names = files
+        .iter()
+        .map(|file| file.to_string_lossy().into_owned())
+        .collect();
+
+    let mut sources = Vec::new();
+    for file in &files {
+        match std::fs::read_to_string(file) {
+            Ok(source) => sources.push(source),
+            Err(e) => {
+                return (
+                    file_names,
+                    vec![diagnostics::emit_error(file, &e.to_string())],
+                )
+            }
+        }
+    }
+
+    let mut problems = Vec::new();
+    for (index, pass_path) in files.iter().enumerate().take(project.manifest.passes.len()) {
+        let source = &sources[index];
+        let shader = match project.parse_pass(index, &sources) {
+            Ok(shader) => shader,
+            Err(e) => {
+                e.emit_to_stderr(source, &pass_path.to_string_lossy());
+                problems.extend(diagnostics::from_shader_error(pass_path, source, &e));
+                continue;
+            }
+        };
+
+        let errors: Vec<_> = interface_errors(&shader.module)
+            .into_iter()
+            .map(|error| Lint {
+                span: project.pass_span(index, &sources, error.span),
+                message: error.message,
+            })
+            .collect();
+        problems.extend(diagnostics::emit_lints(
+            pass_path,
+            source,
+            &errors,
+            Severity::Error,
+        ));
+        problems.extend(diagnostics::emit_lints(
+            pass_path,
+            source,
+            &project.lint_pass(index, &sources),
+            Severity::Warning,
+        ));
+    }
+
+    (file_names, problems)
+}
+
+// where the module does not fit the pipeline of the renderer, which wgpu would only report
+// when creating it
+pub fn interface_errors(module: &naga::Module) -> Vec<Lint> {
+    let mut errors = Vec::new();
+
+    let entry_point = module.entry_points.iter().find(|entry_point| {
+        entry_point.name == FRAGMENT_ENTRY_POINT && entry_point.stage == ShaderStage::Fragment
+    });
+    match entry_point {
+        None => errors.push(Lint {
+            span: naga::Span::default(),
+            message: format!("there is no `@fragment fn {}`", FRAGMENT_ENTRY_POINT),
+        }),
+        Some(entry_point) if !writes_color(module, entry_point.function.result.as_ref()) => errors
+            .push(Lint {
+                span: naga::Span::default(),
+                message: format!(
+                    "`{}` has to return `@location(0) vec4<f32>`, the color of the pixel",
+                    FRAGMENT_ENTRY_POINT
+                ),
+            }),
+        Some(_) => {}
+    }
+
+    for (handle, global) in module.global_variables.iter() {
+        let Some(binding) = global.binding.as_ref() else {
+            continue;
+        };
+        let span = module.global_variables.get_span(handle);
+        let name = global.name.as_deref().unwrap_or("_");
+        let inner = &module.types[global.ty].inner;
+
+        let message = if binding.group != 0 {
+            Some(format!(
+                "`{}` is in group {}, only group 0 is bound",
+                name, binding.group
+            ))
+        } else if binding.binding == 0 {
+            info_error(module, name, global.space, inner)
+        } else if binding.binding > 2 * CHANNEL_COUNT as u32 {
+            Some(format!(
+                "`{}` is at binding {}, only bindings 0 to {} are bound",
+                name,
+                binding.binding,
+                2 * CHANNEL_COUNT
+            ))
+        } else if binding.binding % 2 == 1 {
+            let is_texture = matches!(
+                *inner,
+                TypeInner::Image {
+                    dim: ImageDimension::D2,
+                    arrayed: false,
+                    class: ImageClass::Sampled {
+                        kind: ScalarKind::Float,
+                        multi: false,
+                    },
+                }
+            );
+            (!is_texture).then(|| {
+                format!(
+                    "`{}` is at binding {}, the texture of channel {}, it has to be a `texture_2d<f32>`",
+                    name,
+                    binding.binding,
+                    (binding.binding - 1) / 2
+                )
+            })
+        } else {
+            let is_sampler = matches!(*inner, TypeInner::Sampler { comparison: false });
+            (!is_sampler).then(|| {
+                format!(
+                    "`{}` is at binding {}, the sampler of channel {}, it has to be a `sampler`",
+                    name,
+                    binding.binding,
+                    (binding.binding - 2) / 2
+                )
+            })
+        };
+
+        errors.extend(message.map(|message| Lint { span, message }));
+    }
+
+    errors
+}
+
+// a `vec4<f32>` at location 0, directly or as a member of the returned struct
+fn writes_color(module: &naga::Module, result: Option<&naga::FunctionResult>) -> bool {
+    let is_color = |ty: naga::Handle<naga::Type>, binding: Option<&Binding>| {
+        matches!(binding, Some(Binding::Location { location: 0, .. }))
+            && matches!(
+                module.types[ty].inner,
+                TypeInner::Vector {
+                    size: naga::VectorSize::Quad,
+                    scalar: naga::Scalar::F32,
+                }
+            )
+    };
+
+    let Some(result) = result else {
+        return false;
+    };
+    match module.types[result.ty].inner {
+        TypeInner::Struct { ref members, .. } => members
+            .iter()
+            .any(|member| is_color(member.ty, member.binding.as_ref())),
+        _ => is_color(result.ty, result.binding.as_ref()),
+    }
+}
+
+// binding 0 is the `Info` uniform, of which a shader can declare the first members only
+fn info_error(
+    module: &naga::Module,
+    name: &str,
+    space: AddressSpace,
+    inner: &TypeInner,
+) -> Option<String> {
+    if space != AddressSpace::Uniform {
+        return Some(format!(
+            "`{}` is at binding 0, which is the `Info` uniform, declare it with `var<uniform>`",
+            name
+        ));
+    }
+
+    match *inner {
+        TypeInner::Struct { span, .. } if span > INFO_SIZE => Some(format!(
+            "`{}` is {} bytes, larger than `Info`, which is {} bytes",
+            name, span, INFO_SIZE
+        )),
+        TypeInner::Struct { ref members, .. } => members.iter().find_map(|member| {
+            info_member_error(
+                member.name.as_deref().unwrap_or("_"),
+                member.offset,
+                &module.types[member.ty].inner,
+            )
+        }),
+        // a plain value is read from the start of `Info`
+        _ => info_member_error(name, 0, inner),
+    }
+}
+
+fn info_member_error(name: &str, offset: u32, inner: &TypeInner) -> Option<String> {
+    match InfoMember::from_offset(offset) {
+        Some(member) if member.is_type(inner) => None,
+        Some(member) => Some(format!(
+            "`{}` is at offset {} of `Info`, where `{}` is, which has another type",
+            name,
+            offset,
+            member.name()
+        )),
+        None => Some(format!(
+            "`{}` is at offset {} of `Info`, where no member starts",
+            name, offset
+        )),
+    }
+}
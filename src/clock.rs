@@ -0,0 +1,96 @@
+// the time the shader sees. both clocks of `Info` run at `speed` (negative runs them back) and stop
+// while paused. single frames can be stepped forward or back by `step` seconds, which is what a
+// frame gets as `time_delta` then. clocks do not go below 0.
+
+use std::time::Instant;
+
+pub struct Clock {
+    // seconds
+    pub from_start_up: f64,
+    pub from_update: f64,
+    pub speed: f64,
+    pub paused: bool,
+    pub step: f64,
+    // steps to take with the next frame, negative for back
+    pending_steps: i32,
+    last_tick: Instant,
+}
+
+impl Clock {
+    pub fn new(time: f64, speed: f64, paused: bool, step: f64) -> Self {
+        Self {
+            from_start_up: time.max(0.0),
+            from_update: 0.0,
+            speed,
+            paused,
+            step,
+            pending_steps: 0,
+            last_tick: Instant::now(),
+        }
+    }
+
+    // advance for a new frame, giving the time delta of the shader and how many frames it moved
+    pub fn tick(&mut self) -> (f32, i32) {
+        let elapsed = self.last_tick.elapsed().as_secs_f64();
+        self.last_tick = Instant::now();
+
+        let (delta, frames) = if self.pending_steps != 0 {
+            let steps = std::mem::take(&mut self.pending_steps);
+            (steps as f64 * self.step, steps)
+        } else if self.paused {
+            (0.0, 0)
+        } else {
+            (elapsed * self.speed, 1)
+        };
+
+        self.from_start_up = (self.from_start_up + delta).max(0.0);
+        self.from_update = (self.from_update + delta).max(0.0);
+        (delta as f32, frames)
+    }
+
+    // start measuring the next frame from now, without moving the clocks
+    pub fn restart_tick(&mut self) {
+        self.last_tick = Instant::now();
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    // pauses if running
+    pub fn step_by(&mut self, steps: i32) {
+        self.paused = true;
+        self.pending_steps += steps;
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed;
+    }
+
+    pub fn reset_start_up(&mut self) {
+        self.from_start_up = 0.0;
+    }
+
+    pub fn reset_update(&mut self) {
+        self.from_update = 0.0;
+    }
+
+    // both clocks move by the same amount, so the time since the update keeps its offset
+    pub fn jump_to(&mut self, time: f64) {
+        let time = time.max(0.0);
+        self.from_update = (self.from_update + time - self.from_start_up).max(0.0);
+        self.from_start_up = time;
+    }
+
+    // for the status line
+    pub fn status(&self) -> String {
+        let mut status = format!("Time:{:>8.2}s", self.from_start_up);
+        if self.speed != 1.0 {
+            status.push_str(&format!(" x{}", self.speed));
+        }
+        if self.paused {
+            status.push_str(" (paused)");
+        }
+        status
+    }
+}
@@ -0,0 +1,348 @@
+// static complexity of the fragment entry point, printed on every reload to notice code growing by accident.
+// functions are counted as if inlined at every call, like drivers usually do.
+// the register estimate is the most scalar values alive at once: every value lives from where it is
+// computed to its last use, values used in a loop live through the whole loop, and every local variable
+// takes its registers all the time. compilers do better, so it is for comparing versions of the code.
+
+use std::collections::HashMap;
+
+use naga::valid::{FunctionInfo, ModuleInfo};
+use naga::{Block, Expression, Function, Handle, SampleLevel, Statement, TypeInner};
+
+use crate::shader::FRAGMENT_ENTRY_POINT;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Complexity {
+    pub expressions: usize,
+    pub statements: usize,
+    pub loops: usize,
+    // of loops inside loops, 0 without loops
+    pub loop_depth: usize,
+    pub texture_samples: usize,
+    pub calls: usize,
+    pub registers: usize,
+}
+
+// none without the fragment entry point
+pub fn analyze(module: &naga::Module, info: &ModuleInfo) -> Option<Complexity> {
+    let index = module
+        .entry_points
+        .iter()
+        .position(|entry_point| entry_point.name == FRAGMENT_ENTRY_POINT)?;
+    let mut analyzed = HashMap::new();
+    Some(analyze_function(
+        module,
+        info,
+        &module.entry_points[index].function,
+        info.get_entry_point(index),
+        &mut analyzed,
+    ))
+}
+
+// like `412 expressions, 120 statements, ...`, with the changes from `previous`
+pub fn summary(complexity: &Complexity, previous: Option<&Complexity>) -> String {
+    let count = |value: usize, previous: Option<usize>| match previous {
+        Some(previous) if previous != value => {
+            format!("{} ({:+})", value, value as i64 - previous as i64)
+        }
+        _ => value.to_string(),
+    };
+    let previous = |field: fn(&Complexity) -> usize| previous.map(field);
+    let loops = if complexity.loops == 0 {
+        format!("{} loops", count(0, previous(|c| c.loops)))
+    } else {
+        format!(
+            "{} loops nested {} deep",
+            count(complexity.loops, previous(|c| c.loops)),
+            count(complexity.loop_depth, previous(|c| c.loop_depth))
+        )
+    };
+    format!(
+        "{} expressions, {} statements, {}, {} texture samples, {} calls, ~{} registers",
+        count(complexity.expressions, previous(|c| c.expressions)),
+        count(complexity.statements, previous(|c| c.statements)),
+        loops,
+        count(complexity.texture_samples, previous(|c| c.texture_samples)),
+        count(complexity.calls, previous(|c| c.calls)),
+        count(complexity.registers, previous(|c| c.registers)),
+    )
+}
+
+// where the walk through the statements of a function is
+struct Walk<'a> {
+    module: &'a naga::Module,
+    info: &'a ModuleInfo,
+    analyzed: &'a mut HashMap<Handle<Function>, Complexity>,
+    complexity: Complexity,
+    // the expressions evaluated so far, by index
+    position: usize,
+    loop_depth: usize,
+    // the last position each expression is used at
+    last_use: Vec<usize>,
+    // start and end positions
+    loops: Vec<(usize, usize)>,
+    // positions of calls, with the registers of the function called
+    calls: Vec<(usize, usize)>,
+}
+
+fn analyze_function(
+    module: &naga::Module,
+    info: &ModuleInfo,
+    function: &Function,
+    function_info: &FunctionInfo,
+    analyzed: &mut HashMap<Handle<Function>, Complexity>,
+) -> Complexity {
+    let mut walk = Walk {
+        module,
+        info,
+        analyzed,
+        complexity: Complexity {
+            expressions: function.expressions.len(),
+            ..Default::default()
+        },
+        position: 0,
+        loop_depth: 0,
+        last_use: (0..function.expressions.len()).collect(),
+        loops: Vec::new(),
+        calls: Vec::new(),
+    };
+
+    for (handle, expression) in function.expressions.iter() {
+        if matches!(
+            expression,
+            Expression::ImageSample { .. } | Expression::ImageLoad { .. }
+        ) {
+            walk.complexity.texture_samples += 1;
+        }
+        for operand in operands(expression) {
+            walk.last_use[operand.index()] = walk.last_use[operand.index()].max(handle.index());
+        }
+    }
+    walk.block(&function.body);
+
+    // values from before a loop used in it are needed again in every iteration
+    for &(start, end) in &walk.loops {
+        for (definition, last_use) in walk.last_use.iter_mut().enumerate() {
+            if definition < start && (start..=end).contains(last_use) {
+                *last_use = end;
+            }
+        }
+    }
+
+    // values alive at each position
+    let mut changes = vec![0i64; function.expressions.len() + 1];
+    for (handle, expression) in function.expressions.iter() {
+        let scalars = match expression {
+            // constants, and pointers to variables counted below
+            Expression::Literal(_)
+            | Expression::Constant(_)
+            | Expression::Override(_)
+            | Expression::ZeroValue(_)
+            | Expression::GlobalVariable(_)
+            | Expression::LocalVariable(_) => 0,
+            _ => scalars(function_info[handle].ty.inner_with(&module.types)),
+        };
+        changes[handle.index()] += scalars as i64;
+        changes[walk.last_use[handle.index()]] -= scalars as i64;
+    }
+    let mut alive = Vec::with_capacity(changes.len());
+    let mut count = 0;
+    for change in changes {
+        count += change;
+        alive.push(count.max(0) as usize);
+    }
+
+    let variables: usize = function
+        .local_variables
+        .iter()
+        .map(|(_, variable)| scalars(&module.types[variable.ty].inner))
+        .sum();
+    let calls = walk
+        .calls
+        .iter()
+        .map(|&(position, registers)| alive[position] + registers);
+    walk.complexity.registers = variables + alive.iter().copied().chain(calls).max().unwrap_or(0);
+
+    walk.complexity
+}
+
+impl Walk<'_> {
+    fn block(&mut self, block: &Block) {
+        for statement in block.iter() {
+            if !matches!(statement, Statement::Emit(_)) {
+                self.complexity.statements += 1;
+            }
+            match statement {
+                Statement::Emit(range) => {
+                    if let Some((_, last)) = range.first_and_last() {
+                        self.position = self.position.max(last.index() + 1);
+                    }
+                }
+                Statement::Block(block) => self.block(block),
+                Statement::If {
+                    condition,
+                    accept,
+                    reject,
+                } => {
+                    self.used(*condition);
+                    self.block(accept);
+                    self.block(reject);
+                }
+                Statement::Switch { selector, cases } => {
+                    self.used(*selector);
+                    for case in cases {
+                        self.block(&case.body);
+                    }
+                }
+                Statement::Loop {
+                    body,
+                    continuing,
+                    break_if,
+                } => {
+                    self.complexity.loops += 1;
+                    self.loop_depth += 1;
+                    self.complexity.loop_depth = self.complexity.loop_depth.max(self.loop_depth);
+                    let start = self.position;
+                    self.block(body);
+                    self.block(continuing);
+                    if let Some(break_if) = break_if {
+                        self.used(*break_if);
+                    }
+                    self.loops.push((start, self.position));
+                    self.loop_depth -= 1;
+                }
+                Statement::Return { value: Some(value) } => self.used(*value),
+                Statement::Store { pointer, value } => {
+                    self.used(*pointer);
+                    self.used(*value);
+                }
+                Statement::Call {
+                    function,
+                    arguments,
+                    ..
+                } => {
+                    for argument in arguments {
+                        self.used(*argument);
+                    }
+                    self.call(*function);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn used(&mut self, expression: Handle<Expression>) {
+        let last_use = &mut self.last_use[expression.index()];
+        *last_use = (*last_use).max(self.position);
+    }
+
+    // count the function as if its code was here
+    fn call(&mut self, handle: Handle<Function>) {
+        let callee = match self.analyzed.get(&handle) {
+            Some(callee) => *callee,
+            None => {
+                let callee = analyze_function(
+                    self.module,
+                    self.info,
+                    &self.module.functions[handle],
+                    &self.info[handle],
+                    self.analyzed,
+                );
+                self.analyzed.insert(handle, callee);
+                callee
+            }
+        };
+
+        let complexity = &mut self.complexity;
+        complexity.expressions += callee.expressions;
+        complexity.statements += callee.statements;
+        complexity.loops += callee.loops;
+        complexity.loop_depth = complexity
+            .loop_depth
+            .max(self.loop_depth + callee.loop_depth);
+        complexity.texture_samples += callee.texture_samples;
+        complexity.calls += 1 + callee.calls;
+        self.calls.push((self.position, callee.registers));
+    }
+}
+
+// the expressions an expression reads
+fn operands(expression: &Expression) -> Vec<Handle<Expression>> {
+    match *expression {
+        Expression::Compose { ref components, .. } => components.clone(),
+        Expression::Access { base, index } => vec![base, index],
+        Expression::AccessIndex { base, .. } => vec![base],
+        Expression::Splat { value, .. } => vec![value],
+        Expression::Swizzle { vector, .. } => vec![vector],
+        Expression::Load { pointer } => vec![pointer],
+        Expression::ImageSample {
+            image,
+            sampler,
+            coordinate,
+            array_index,
+            offset,
+            level,
+            depth_ref,
+            ..
+        } => {
+            let level = match level {
+                SampleLevel::Auto | SampleLevel::Zero => vec![],
+                SampleLevel::Exact(level) | SampleLevel::Bias(level) => vec![level],
+                SampleLevel::Gradient { x, y } => vec![x, y],
+            };
+            [image, sampler, coordinate]
+                .into_iter()
+                .chain(array_index)
+                .chain(offset)
+                .chain(depth_ref)
+                .chain(level)
+                .collect()
+        }
+        Expression::ImageLoad {
+            image,
+            coordinate,
+            array_index,
+            sample,
+            level,
+        } => [image, coordinate]
+            .into_iter()
+            .chain(array_index)
+            .chain(sample)
+            .chain(level)
+            .collect(),
+        Expression::ImageQuery { image, .. } => vec![image],
+        Expression::Unary { expr, .. }
+        | Expression::Derivative { expr, .. }
+        | Expression::As { expr, .. } => vec![expr],
+        Expression::Binary { left, right, .. } => vec![left, right],
+        Expression::Select {
+            condition,
+            accept,
+            reject,
+        } => vec![condition, accept, reject],
+        Expression::Relational { argument, .. } => vec![argument],
+        Expression::Math {
+            arg,
+            arg1,
+            arg2,
+            arg3,
+            ..
+        } => std::iter::once(arg)
+            .chain(arg1)
+            .chain(arg2)
+            .chain(arg3)
+            .collect(),
+        Expression::ArrayLength(expr) => vec![expr],
+        _ => Vec::new(),
+    }
+}
+
+// scalar registers a value of the type takes, 0 for the ones kept in memory
+fn scalars(inner: &TypeInner) -> usize {
+    match *inner {
+        TypeInner::Scalar(_) => 1,
+        TypeInner::Vector { size, .. } => size as usize,
+        TypeInner::Matrix { columns, rows, .. } => columns as usize * rows as usize,
+        _ => 0,
+    }
+}
@@ -0,0 +1,202 @@
+// translate the fragment shader to the other shading languages with naga's backends.
+
+use std::path::{Path, PathBuf};
+
+use crate::shader::{ParsedShader, FRAGMENT_ENTRY_POINT};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    GlslEs,
+    Glsl,
+    Hlsl,
+    Msl,
+    SpirV,
+}
+
+impl Target {
+    pub const ALL: [Target; 5] = [
+        Target::GlslEs,
+        Target::Glsl,
+        Target::Hlsl,
+        Target::Msl,
+        Target::SpirV,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::GlslEs => "GLSL ES 3.00",
+            Target::Glsl => "GLSL 4.50",
+            Target::Hlsl => "HLSL SM 5.1",
+            Target::Msl => "MSL 2.0",
+            Target::SpirV => "SPIR-V",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Target::GlslEs => "gles.glsl",
+            Target::Glsl => "gl.glsl",
+            Target::Hlsl => "hlsl",
+            Target::Msl => "metal",
+            Target::SpirV => "spvasm",
+        }
+    }
+
+    // `<dir of shader>/<shader name>.<ext>`
+    pub fn output_path(self, shader_path: &Path) -> PathBuf {
+        let stem = shader_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "live".to_string());
+
+        shader_path.with_file_name(format!("{}.{}", stem, self.extension()))
+    }
+}
+
+// `live-wgsl cross`
+pub fn run(path: &str, print: bool) -> std::process::ExitCode {
+    let Some((_, shader)) = crate::shader::load(path) else {
+        return std::process::ExitCode::FAILURE;
+    };
+
+    let mut failed = false;
+    if print {
+        for target in Target::ALL {
+            match translate(&shader, target) {
+                Ok(code) => println!("// ---- {} ----\n\n{}", target.name(), code),
+                Err(e) => {
+                    eprintln!("{} failed: {}", target.name(), e);
+                    failed = true;
+                }
+            }
+        }
+    } else {
+        let results = write_next_to(Path::new(path), &shader);
+        print_written(&results);
+        failed = results.iter().any(|(_, result)| result.is_err());
+    }
+
+    if failed {
+        std::process::ExitCode::FAILURE
+    } else {
+        std::process::ExitCode::SUCCESS
+    }
+}
+
+pub fn translate(shader: &ParsedShader, target: Target) -> Result<String, String> {
+    let (module, info) = (&shader.module, &shader.info);
+    match target {
+        Target::GlslEs => write_glsl(module, info, naga::back::glsl::Version::new_gles(300)),
+        Target::Glsl => write_glsl(module, info, naga::back::glsl::Version::Desktop(450)),
+        Target::Hlsl => write_hlsl(module, info),
+        Target::Msl => write_msl(module, info),
+        Target::SpirV => write_spirv(module, info),
+    }
+}
+
+// write every translation next to the shader file.
+// a target failing does not stop the others, the errors are returned per target instead.
+pub fn write_next_to(
+    shader_path: &Path,
+    shader: &ParsedShader,
+) -> Vec<(Target, Result<PathBuf, String>)> {
+    Target::ALL
+        .iter()
+        .map(|&target| {
+            let result = translate(shader, target).and_then(|code| {
+                let path = target.output_path(shader_path);
+                std::fs::write(&path, code)
+                    .map(|_| path)
+                    .map_err(|e| e.to_string())
+            });
+            (target, result)
+        })
+        .collect()
+}
+
+pub fn print_written(results: &[(Target, Result<PathBuf, String>)]) {
+    for (target, result) in results {
+        match result {
+            Ok(path) => println!("{:<13} {}", target.name(), path.display()),
+            Err(e) => eprintln!("{:<13} failed: {}", target.name(), e),
+        }
+    }
+}
+
+pub fn write_glsl(
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+    version: naga::back::glsl::Version,
+) -> Result<String, String> {
+    let options = naga::back::glsl::Options {
+        version,
+        ..Default::default()
+    };
+    let pipeline_options = naga::back::glsl::PipelineOptions {
+        shader_stage: naga::ShaderStage::Fragment,
+        entry_point: FRAGMENT_ENTRY_POINT.to_string(),
+        multiview: None,
+    };
+
+    let mut code = String::new();
+    naga::back::glsl::Writer::new(
+        &mut code,
+        module,
+        info,
+        &options,
+        &pipeline_options,
+        naga::proc::BoundsCheckPolicies::default(),
+    )
+    .and_then(|mut writer| writer.write())
+    .map_err(|e| e.to_string())?;
+
+    Ok(code)
+}
+
+pub fn write_hlsl(module: &naga::Module, info: &naga::valid::ModuleInfo) -> Result<String, String> {
+    let options = naga::back::hlsl::Options::default();
+
+    let mut code = String::new();
+    naga::back::hlsl::Writer::new(&mut code, &options)
+        .write(module, info, None)
+        .map_err(|e| e.to_string())?;
+
+    Ok(code)
+}
+
+pub fn write_msl(module: &naga::Module, info: &naga::valid::ModuleInfo) -> Result<String, String> {
+    let options = naga::back::msl::Options {
+        lang_version: (2, 0),
+        ..Default::default()
+    };
+
+    naga::back::msl::write_string(
+        module,
+        info,
+        &options,
+        &naga::back::msl::PipelineOptions::default(),
+    )
+    .map(|(code, _)| code)
+    .map_err(|e| e.to_string())
+}
+
+pub fn write_spirv(
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+) -> Result<String, String> {
+    let words = naga::back::spv::write_vec(
+        module,
+        info,
+        &naga::back::spv::Options::default(),
+        Some(&naga::back::spv::PipelineOptions {
+            shader_stage: naga::ShaderStage::Fragment,
+            entry_point: FRAGMENT_ENTRY_POINT.to_string(),
+        }),
+    )
+    .map_err(|e| e.to_string())?;
+
+    // naga only writes binary, disassemble it to make it readable
+    let module = rspirv::dr::load_words(words).map_err(|e| e.to_string())?;
+
+    Ok(rspirv::binary::Disassemble::disassemble(&module))
+}
@@ -0,0 +1,380 @@
+// report compile attempts of the live shader.
+// on the terminal, each attempt starts on a cleared screen (or a new section with `--no-clear`),
+// so errors of older attempts do not pile up under the render time line.
+// for editors, each attempt can also be written as a json line and to a quickfix file.
+
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use codespan_reporting::files::SimpleFile;
+use codespan_reporting::term::{self, termcolor};
+use serde::Serialize;
+
+use crate::lint::Lint;
+use crate::shader::ShaderError;
+
+pub struct Diagnostics {
+    clear: bool,
+    // printed again on top of each cleared screen
+    header: String,
+    // the adapter rendering, printed below the header
+    gpu: Option<String>,
+    // every compile result as a json line
+    json: Option<std::fs::File>,
+    // `file:line:column: severity: message` lines, emptied by a successful compile
+    quickfix: Option<PathBuf>,
+    // every file of the project, so editors can clear the ones without problems
+    files: Vec<String>,
+    // of the last compile, for the overlay of the window
+    current: Vec<Diagnostic>,
+    // warnings of the compile going on, reported by `success`
+    warnings: Vec<Diagnostic>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    // errors of wgpu have no location
+    pub range: Option<Range>,
+    pub severity: Severity,
+    pub message: String,
+    pub notes: Vec<String>,
+}
+
+// 1-based, columns count characters
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+// a json line, also printed by `check`
+#[derive(Serialize)]
+pub struct CompileResult<'a> {
+    pub ok: bool,
+    pub files: &'a [String],
+    pub diagnostics: &'a [Diagnostic],
+}
+
+impl Diagnostics {
+    pub fn new(
+        clear: bool,
+        header: String,
+        json: Option<std::fs::File>,
+        quickfix: Option<PathBuf>,
+        files: Vec<String>,
+    ) -> Self {
+        Self {
+            // clearing a file or a pipe would only fill it with escape codes
+            clear: clear && std::io::stdout().is_terminal(),
+            header,
+            gpu: None,
+            json,
+            quickfix,
+            files,
+            current: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn set_gpu(&mut self, description: String) {
+        self.gpu = Some(description);
+    }
+
+    // start the report of a compile attempt
+    pub fn begin(&mut self, time: Option<&str>) {
+        self.warnings.clear();
+        if self.clear {
+            // clear the screen and move the cursor to the top, the scrollback keeps earlier compiles
+            print!("\x1b[2J\x1b[H{}", self.header);
+            if let Some(gpu) = self.gpu.as_deref() {
+                println!("GPU: {}\n", gpu);
+            }
+        } else {
+            println!("\n\n---------- {} ----------\n", time.unwrap_or("start"));
+        }
+    }
+
+    pub fn shader_error(&mut self, path: &Path, source: &str, error: &ShaderError) {
+        error.emit_to_stderr(source, &path.to_string_lossy());
+        self.write(false, &from_shader_error(path, source, error));
+    }
+
+    pub fn gpu_error(&mut self, path: &Path, error: &wgpu::Error) {
+        self.error(path, &error.to_string());
+    }
+
+    // an error without location
+    pub fn error(&mut self, path: &Path, description: &str) {
+        let diagnostic = emit_error(path, description);
+        self.write(false, &[diagnostic]);
+    }
+
+    // lints of a file, printed now and reported with the next `success`
+    pub fn warnings(&mut self, path: &Path, source: &str, lints: &[Lint]) {
+        self.warnings
+            .extend(emit_lints(path, source, lints, Severity::Warning));
+    }
+
+    pub fn success(&mut self, name: &str, time: Option<&str>) {
+        match time {
+            Some(time) => println!("Compiled: {} ({})\n", name, time),
+            None => println!("Compiled: {}\n", name),
+        }
+        let warnings = std::mem::take(&mut self.warnings);
+        self.write(true, &warnings);
+    }
+
+    // the errors of the last compile as lines of text, none after a successful one
+    pub fn overlay_text(&self) -> Option<String> {
+        let mut errors = self
+            .current
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .peekable();
+        errors.peek()?;
+
+        let mut text = String::new();
+        for diagnostic in errors {
+            let file = Path::new(&diagnostic.file)
+                .file_name()
+                .map_or(diagnostic.file.as_str(), |name| {
+                    name.to_str().unwrap_or_default()
+                });
+            match diagnostic.range {
+                Some(range) => text.push_str(&format!(
+                    "{}:{}:{}: {}\n",
+                    file, range.start.line, range.start.column, diagnostic.message
+                )),
+                None => text.push_str(&format!("{}: {}\n", file, diagnostic.message)),
+            }
+            for note in &diagnostic.notes {
+                text.push_str(&format!("  {}\n", note));
+            }
+        }
+        Some(text)
+    }
+
+    fn write(&mut self, ok: bool, diagnostics: &[Diagnostic]) {
+        self.current = diagnostics.to_vec();
+
+        if let Some(json) = self.json.as_mut() {
+            let result = CompileResult {
+                ok,
+                files: &self.files,
+                diagnostics,
+            };
+            serde_json::to_writer(&mut *json, &result)
+                .map_err(std::io::Error::from)
+                .and_then(|_| writeln!(json))
+                .and_then(|_| json.flush())
+                .map_err(|e| eprintln!("Error when writing diagnostics:\n{}", e))
+                .ok();
+        }
+
+        if let Some(path) = self.quickfix.as_ref() {
+            std::fs::write(path, quickfix(diagnostics))
+                .map_err(|e| eprintln!("Error when writing file: {}\n{}", path.display(), e))
+                .ok();
+        }
+    }
+}
+
+pub fn from_shader_error(path: &Path, source: &str, error: &ShaderError) -> Vec<Diagnostic> {
+    let file = path.to_string_lossy().into_owned();
+
+    match error {
+        ShaderError::Parse(e) => {
+            vec![Diagnostic {
+                file,
+                range: e.labels().next().and_then(|(span, _)| range(source, span)),
+                severity: Severity::Error,
+                message: e.message().to_string(),
+                notes: e
+                    .labels()
+                    .map(|(_, label)| label.to_string())
+                    .filter(|label| !label.is_empty())
+                    .collect(),
+            }]
+        }
+        ShaderError::GlslParse(e) => e
+            .errors
+            .iter()
+            .map(|error| Diagnostic {
+                file: file.clone(),
+                range: range(source, error.meta),
+                severity: Severity::Error,
+                message: error.kind.to_string(),
+                notes: Vec::new(),
+            })
+            .collect(),
+        ShaderError::Validation(e) => {
+            let mut notes: Vec<_> = e.spans().map(|(_, label)| label.clone()).collect();
+            let mut cause = std::error::Error::source(e.as_inner());
+            while let Some(error) = cause {
+                notes.push(error.to_string());
+                cause = error.source();
+            }
+
+            vec![Diagnostic {
+                file,
+                range: e.spans().next().and_then(|(span, _)| range(source, *span)),
+                severity: Severity::Error,
+                message: e.as_inner().to_string(),
+                notes,
+            }]
+        }
+    }
+}
+
+// an error without location, printed and returned. the first line becomes the message and the rest notes
+pub fn emit_error(path: &Path, description: &str) -> Diagnostic {
+    let mut lines = description
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+
+    let diagnostic = Diagnostic {
+        file: path.to_string_lossy().into_owned(),
+        range: None,
+        severity: Severity::Error,
+        message: lines.next().unwrap_or("unknown error").to_string(),
+        notes: lines.map(str::to_string).collect(),
+    };
+
+    emit(
+        &SimpleFile::new(String::new(), ""),
+        &codespan_reporting::diagnostic::Diagnostic::error()
+            .with_message(&diagnostic.message)
+            .with_notes(
+                std::iter::once(format!("in {}", diagnostic.file))
+                    .chain(diagnostic.notes.iter().cloned())
+                    .collect(),
+            ),
+    );
+    diagnostic
+}
+
+// problems with a span in the code, printed and returned with the given severity
+pub fn emit_lints(
+    path: &Path,
+    source: &str,
+    lints: &[Lint],
+    severity: Severity,
+) -> Vec<Diagnostic> {
+    let file = SimpleFile::new(path.to_string_lossy().into_owned(), source);
+    lints
+        .iter()
+        .map(|lint| {
+            let range = lint.span.to_range().map(|range| first_line(source, range));
+
+            let mut diagnostic = match severity {
+                Severity::Error => codespan_reporting::diagnostic::Diagnostic::error(),
+                Severity::Warning => codespan_reporting::diagnostic::Diagnostic::warning(),
+            }
+            .with_message(&lint.message);
+            diagnostic =
+                match range.clone() {
+                    Some(range) => diagnostic.with_labels(vec![
+                        codespan_reporting::diagnostic::Label::primary((), range),
+                    ]),
+                    None => diagnostic.with_notes(vec![format!("in {}", file.name())]),
+                };
+            emit(&file, &diagnostic);
+
+            Diagnostic {
+                file: file.name().clone(),
+                range: range.map(|range| Range {
+                    start: position(source, range.start),
+                    end: position(source, range.end),
+                }),
+                severity,
+                message: lint.message.clone(),
+                notes: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+// gcc style, which vim's default `errorformat` and most problem matchers read
+pub fn quickfix(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let start = diagnostic
+                .range
+                .map_or(Position { line: 1, column: 1 }, |range| range.start);
+            let severity = match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            format!(
+                "{}:{}:{}: {}: {}\n",
+                diagnostic.file,
+                start.line,
+                start.column,
+                severity,
+                diagnostic.message.replace('\n', " ")
+            )
+        })
+        .collect()
+}
+
+fn range(source: &str, span: naga::Span) -> Option<Range> {
+    let range = span.to_range()?;
+    Some(Range {
+        start: position(source, range.start),
+        end: position(source, range.end),
+    })
+}
+
+fn position(source: &str, offset: usize) -> Position {
+    let before = source.get(..offset).unwrap_or(source);
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+// long spans like whole functions are cut to their first line
+fn first_line(source: &str, range: std::ops::Range<usize>) -> std::ops::Range<usize> {
+    let end = source
+        .get(range.clone())
+        .and_then(|code| code.find('\n'))
+        .map_or(range.end, |index| range.start + index);
+    range.start..end
+}
+
+fn emit(
+    file: &SimpleFile<String, &str>,
+    diagnostic: &codespan_reporting::diagnostic::Diagnostic<()>,
+) {
+    // no escape codes in redirected output
+    let color_choice = if std::io::stderr().is_terminal() {
+        termcolor::ColorChoice::Auto
+    } else {
+        termcolor::ColorChoice::Never
+    };
+    let writer = termcolor::StandardStream::stderr(color_choice);
+    term::emit(
+        &mut writer.lock(),
+        &term::Config::default(),
+        file,
+        diagnostic,
+    )
+    .ok();
+}
@@ -0,0 +1,200 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// 8-bit PNG
+    Png8,
+    /// 16-bit PNG
+    Png16,
+    /// 16-bit (half) float OpenEXR
+    Exr16,
+    /// 32-bit float OpenEXR
+    Exr32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportEncoding {
+    /// write the shader output as is
+    Linear,
+    /// apply the sRGB transfer function, like the window surface does
+    Srgb,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Png8 | ExportFormat::Png16 => "png",
+            ExportFormat::Exr16 | ExportFormat::Exr32 => "exr",
+        }
+    }
+
+    // png viewers expect sRGB, compositing tools expect linear exr.
+    pub fn default_encoding(self) -> ExportEncoding {
+        match self {
+            ExportFormat::Png8 | ExportFormat::Png16 => ExportEncoding::Srgb,
+            ExportFormat::Exr16 | ExportFormat::Exr32 => ExportEncoding::Linear,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    pub encoding: Option<ExportEncoding>,
+}
+
+impl ExportSettings {
+    pub fn encoding(&self) -> ExportEncoding {
+        self.encoding.unwrap_or(self.format.default_encoding())
+    }
+
+    // `<dir of shader>/<shader name>-<YYYYmmdd-HHMMSS>.<ext>`
+    pub fn output_path(&self, shader_path: &Path) -> PathBuf {
+        let stem = shader_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "live".to_string());
+        let time = chrono::Local::now().format("%Y%m%d-%H%M%S");
+
+        shader_path.with_file_name(format!("{}-{}.{}", stem, time, self.format.extension()))
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Png(png::EncodingError),
+    Exr(exr::error::Error),
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "io error: {}", e),
+            ExportError::Png(e) => write!(f, "png encoding error: {}", e),
+            ExportError::Exr(e) => write!(f, "exr encoding error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for ExportError {
+    fn from(e: png::EncodingError) -> Self {
+        ExportError::Png(e)
+    }
+}
+
+impl From<exr::error::Error> for ExportError {
+    fn from(e: exr::error::Error) -> Self {
+        ExportError::Exr(e)
+    }
+}
+
+// `pixels` are linear rgba rows, top to bottom, as read back from the float target.
+pub fn export_image(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[[f32; 4]],
+    settings: &ExportSettings,
+) -> Result<(), ExportError> {
+    let encoding = settings.encoding();
+
+    let pixels: Vec<[f32; 4]> = pixels
+        .iter()
+        .map(|&[r, g, b, a]| match encoding {
+            ExportEncoding::Linear => [r, g, b, a],
+            ExportEncoding::Srgb => [linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a],
+        })
+        .collect();
+
+    match settings.format {
+        ExportFormat::Png8 => {
+            write_png(path, width, height, &pixels, encoding, png::BitDepth::Eight)
+        }
+        ExportFormat::Png16 => write_png(
+            path,
+            width,
+            height,
+            &pixels,
+            encoding,
+            png::BitDepth::Sixteen,
+        ),
+        ExportFormat::Exr16 => write_exr(path, width, height, &pixels, |v| {
+            exr::prelude::f16::from_f32(v)
+        }),
+        ExportFormat::Exr32 => write_exr(path, width, height, &pixels, |v| v),
+    }
+}
+
+fn write_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[[f32; 4]],
+    encoding: ExportEncoding,
+    depth: png::BitDepth,
+) -> Result<(), ExportError> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(depth);
+    match encoding {
+        ExportEncoding::Linear => encoder.set_source_gamma(png::ScaledFloat::new(1.0)),
+        ExportEncoding::Srgb => encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual),
+    }
+
+    // png can not hold values outside of 0..1
+    let data: Vec<u8> = match depth {
+        png::BitDepth::Sixteen => pixels
+            .iter()
+            .flatten()
+            .flat_map(|&v| ((v.clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes())
+            .collect(),
+        _ => pixels
+            .iter()
+            .flatten()
+            .map(|&v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect(),
+    };
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+fn write_exr<T: exr::prelude::IntoSample>(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[[f32; 4]],
+    convert: impl Sync + Fn(f32) -> T,
+) -> Result<(), ExportError> {
+    exr::prelude::write_rgba_file(path, width as usize, height as usize, |x, y| {
+        let [r, g, b, a] = pixels[y * width as usize + x];
+        (convert(r), convert(g), convert(b), convert(a))
+    })?;
+
+    Ok(())
+}
+
+// keeps the sign and extends above 1.0, so hdr values survive in float formats
+fn linear_to_srgb(v: f32) -> f32 {
+    let x = v.abs();
+    let encoded = if x <= 0.0031308 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    };
+    encoded.copysign(v)
+}
@@ -0,0 +1,208 @@
+// write a single html file running the shader with webgpu in the browser.
+// the page replicates what `Renderer` does: the pass through quad, 4x msaa, the `ViewportInfo` uniform
+// and the channels, with the images of a project embedded as data urls.
+// only one pass is rendered, so projects with buffers are rejected.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::gpu::renderer::CHANNEL_COUNT;
+use crate::project::{ChannelInput, ChannelManifest, Filter, Project, Wrap};
+use crate::shader::Language;
+
+const TEMPLATE: &str = include_str!("./export_html.html");
+const VERTEX_CODE: &str = include_str!("./gpu/vertex_pass_through.wgsl");
+
+// an image channel, named like in webgpu
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PageChannel {
+    image: String,
+    filter: &'static str,
+    address_mode: &'static str,
+    vflip: bool,
+    srgb: bool,
+}
+
+// `live-wgsl export-html`
+pub fn run(path: &str, output: Option<&str>) -> std::process::ExitCode {
+    let project = if Project::is_project_path(Path::new(path)) {
+        match Project::load(Path::new(path)) {
+            Ok(project) => project,
+            Err(e) => {
+                eprintln!("Error when loading project:\n{}", e);
+                return std::process::ExitCode::FAILURE;
+            }
+        }
+    } else {
+        Project::single(Path::new(path))
+    };
+
+    // catch errors here, the page could only show them in the browser console
+    let (code, channels) = match export(&project) {
+        Ok(page_input) => page_input,
+        Err(e) => {
+            eprintln!("Error when exporting to html:\n{}", e);
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+
+    let output = match output {
+        Some(output) => Path::new(output).to_path_buf(),
+        None => Path::new(path).with_extension("html"),
+    };
+
+    let title = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "live-wgsl".to_string());
+
+    match std::fs::write(&output, page(&title, &code, &channels)) {
+        Ok(()) => {
+            println!("Exported: {}", output.display());
+            std::process::ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error when writing file: {}\n{}", output.display(), e);
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+// the wgsl code of the pass and its channels
+fn export(project: &Project) -> Result<(String, Vec<Option<PageChannel>>), String> {
+    if project.manifest.passes.len() > 1 {
+        return Err(format!(
+            "The project has {} passes, the page renders only one, without buffers",
+            project.manifest.passes.len()
+        ));
+    }
+
+    let sources = project
+        .files()
+        .iter()
+        .map(|file| {
+            std::fs::read_to_string(file)
+                .map_err(|e| format!("Error when reading file: {}\n{}", file.display(), e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let shader = project.parse_pass(0, &sources).map_err(|e| {
+        e.emit_to_stderr(&sources[0], &project.pass_path(0).to_string_lossy());
+        "The shader has errors".to_string()
+    })?;
+
+    // browsers only take wgsl, and the common code is in the module only
+    let code = if project.language(0) == Language::Wgsl && project.manifest.common.is_none() {
+        sources[0].clone()
+    } else {
+        crate::shadertoy::import::to_wgsl(&shader)
+            .map_err(|e| format!("Error when converting to wgsl:\n{}", e))?
+    };
+
+    let pass = &project.manifest.passes[0];
+    let channels = (0..CHANNEL_COUNT)
+        .map(|index| match pass.channels.get(index) {
+            Some(Some(channel)) => page_channel(project, index, channel).map(Some),
+            _ => Ok(None),
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((code, channels))
+}
+
+fn page_channel(
+    project: &Project,
+    index: usize,
+    channel: &ChannelManifest,
+) -> Result<PageChannel, String> {
+    let path = match &channel.input {
+        ChannelInput::Image { path } => project.root.join(path),
+        ChannelInput::Buffer { pass } => {
+            return Err(format!(
+                "iChannel{} reads the buffer of `{}`, the page has no buffers",
+                index, pass
+            ))
+        }
+    };
+
+    let bytes = std::fs::read(&path)
+        .map_err(|e| format!("Error when reading image: {}\n{}", path.display(), e))?;
+    let format = image::guess_format(&bytes)
+        .map_err(|e| format!("Error when reading image: {}\n{}", path.display(), e))?;
+
+    Ok(PageChannel {
+        image: format!("data:{};base64,{}", format.to_mime_type(), base64(&bytes)),
+        filter: match channel.filter {
+            Filter::Nearest => "nearest",
+            Filter::Linear => "linear",
+        },
+        address_mode: match channel.wrap {
+            Wrap::Clamp => "clamp-to-edge",
+            Wrap::Repeat => "repeat",
+        },
+        vflip: channel.vflip,
+        srgb: channel.srgb,
+    })
+}
+
+fn page(title: &str, fragment_code: &str, channels: &[Option<PageChannel>]) -> String {
+    // json is a js expression, and without `<` it is also safe inside a <script> element
+    let channels = serde_json::to_string(channels)
+        .unwrap()
+        .replace('<', "\\u003c");
+    TEMPLATE
+        .replace("{{TITLE}}", &escape_html(title))
+        .replace("{{VERTEX}}", &js_string(VERTEX_CODE))
+        .replace("{{FRAGMENT}}", &js_string(fragment_code))
+        .replace("{{CHANNELS}}", &channels)
+}
+
+// standard base64 with padding
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk.iter().enumerate().fold(0u32, |triple, (i, &byte)| {
+            triple | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(triple >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// a double quoted js string literal, which is also safe inside a <script> element
+fn js_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '<' => out.push_str("\\u003c"),
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,156 @@
+// optional gpu features shaders can use, like `enable f16;`, push constants or 64-bit integers.
+// devices are requested with the ones the adapter supports, shaders are validated as if all of them
+// were there, and a shader needing one the device lacks is reported by its name before it reaches wgpu.
+// push constants hold the same `Info` as the uniform buffer.
+
+use naga::valid::Capabilities;
+use naga::{AddressSpace, Binding, BuiltIn, Scalar, ScalarKind, TypeInner};
+
+pub struct OptionalFeature {
+    pub name: &'static str,
+    pub feature: wgpu::Features,
+    pub capabilities: Capabilities,
+}
+
+pub const OPTIONAL_FEATURES: &[OptionalFeature] = &[
+    OptionalFeature {
+        name: "16-bit floats (`enable f16;`, `shader-f16`)",
+        feature: wgpu::Features::SHADER_F16,
+        capabilities: Capabilities::SHADER_FLOAT16,
+    },
+    OptionalFeature {
+        name: "64-bit floats (`f64`)",
+        feature: wgpu::Features::SHADER_F64,
+        capabilities: Capabilities::FLOAT64,
+    },
+    OptionalFeature {
+        name: "64-bit integers (`i64`, `u64`)",
+        feature: wgpu::Features::SHADER_INT64,
+        capabilities: Capabilities::SHADER_INT64,
+    },
+    OptionalFeature {
+        name: "64-bit integer atomic min and max",
+        feature: wgpu::Features::SHADER_INT64_ATOMIC_MIN_MAX,
+        capabilities: Capabilities::SHADER_INT64_ATOMIC_MIN_MAX,
+    },
+    OptionalFeature {
+        name: "64-bit integer atomics",
+        feature: wgpu::Features::SHADER_INT64_ATOMIC_ALL_OPS,
+        capabilities: Capabilities::SHADER_INT64_ATOMIC_ALL_OPS,
+    },
+    OptionalFeature {
+        name: "push constants (`var<push_constant>`)",
+        feature: wgpu::Features::PUSH_CONSTANTS,
+        capabilities: Capabilities::PUSH_CONSTANT,
+    },
+    OptionalFeature {
+        name: "the primitive index (`@builtin(primitive_index)`)",
+        feature: wgpu::Features::SHADER_PRIMITIVE_INDEX,
+        capabilities: Capabilities::PRIMITIVE_INDEX,
+    },
+];
+
+// requested from the adapter where it has them
+pub fn all() -> wgpu::Features {
+    OPTIONAL_FEATURES
+        .iter()
+        .fold(wgpu::Features::empty(), |all, optional| {
+            all | optional.feature
+        })
+}
+
+// for validating shaders without a device
+pub fn capabilities() -> Capabilities {
+    OPTIONAL_FEATURES
+        .iter()
+        .fold(Capabilities::default(), |all, optional| {
+            all | optional.capabilities
+        })
+}
+
+// the optional features the module uses
+pub fn required(module: &naga::Module) -> wgpu::Features {
+    let mut features = wgpu::Features::empty();
+    let scalar = |scalar: Scalar| match (scalar.kind, scalar.width) {
+        (ScalarKind::Float, 2) => wgpu::Features::SHADER_F16,
+        (ScalarKind::Float, 8) => wgpu::Features::SHADER_F64,
+        (ScalarKind::Sint | ScalarKind::Uint, 8) => wgpu::Features::SHADER_INT64,
+        _ => wgpu::Features::empty(),
+    };
+    for (_, ty) in module.types.iter() {
+        match ty.inner {
+            TypeInner::Scalar(s)
+            | TypeInner::Vector { scalar: s, .. }
+            | TypeInner::Matrix { scalar: s, .. } => features |= scalar(s),
+            // min and max are the least drivers have, more is reported by wgpu
+            TypeInner::Atomic(s) if s.width == 8 => {
+                features |= scalar(s) | wgpu::Features::SHADER_INT64_ATOMIC_MIN_MAX
+            }
+            _ => {}
+        }
+    }
+
+    if module
+        .global_variables
+        .iter()
+        .any(|(_, variable)| variable.space == AddressSpace::PushConstant)
+    {
+        features |= wgpu::Features::PUSH_CONSTANTS;
+    }
+
+    // builtins are arguments of entry points, or members of structs which are
+    let primitive_index = |binding: &Option<Binding>| {
+        matches!(binding, Some(Binding::BuiltIn(BuiltIn::PrimitiveIndex)))
+    };
+    for entry_point in &module.entry_points {
+        for argument in &entry_point.function.arguments {
+            let members = match &module.types[argument.ty].inner {
+                TypeInner::Struct { members, .. } => members.as_slice(),
+                _ => &[],
+            };
+            if primitive_index(&argument.binding)
+                || members
+                    .iter()
+                    .any(|member| primitive_index(&member.binding))
+            {
+                features |= wgpu::Features::SHADER_PRIMITIVE_INDEX;
+            }
+        }
+    }
+
+    features
+}
+
+// names of the features the module uses which are not `available`
+pub fn missing(module: &naga::Module, available: wgpu::Features) -> Vec<&'static str> {
+    let missing = required(module) - available;
+    OPTIONAL_FEATURES
+        .iter()
+        .filter(|optional| missing.contains(optional.feature))
+        .map(|optional| optional.name)
+        .collect()
+}
+
+// the index of the first pass using features which are not `available`, and the error naming them
+pub fn check_passes(
+    sources: &[wgpu::ShaderSource],
+    available: wgpu::Features,
+) -> Result<(), (usize, String)> {
+    for (index, source) in sources.iter().enumerate() {
+        let wgpu::ShaderSource::Naga(module) = source else {
+            continue;
+        };
+        let missing = missing(module, available);
+        if !missing.is_empty() {
+            return Err((
+                index,
+                format!(
+                    "The shader uses {}, which the GPU does not support\n\
+                     see `--list-adapters` for the features of each adapter",
+                    missing.join(", ")
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,10 @@
+void mainImage(out vec4 fragColor, in vec2 fragCoord)
+{
+    // normalized pixel coordinates (from 0 to 1)
+    vec2 uv = fragCoord / iResolution.xy;
+
+    // time varying pixel color
+    vec3 col = 0.5 + 0.5 * cos(iTime + uv.xyx + vec3(0, 2, 4));
+
+    fragColor = vec4(col, 1.0);
+}
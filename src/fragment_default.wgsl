@@ -2,6 +2,9 @@ struct Info {
     viewport_size: vec2<f32>,
     time_from_start_up: f32,
     time_from_update: f32,
+    mouse: vec4<f32>,
+    frame: u32,
+    time_delta: f32,
 }
 
 @group(0) @binding(0)
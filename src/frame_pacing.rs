@@ -0,0 +1,214 @@
+// when the window renders the next frame: as often as the present mode lets it, at most `max_fps`,
+// fewer while the window is not focused, and not at all while it is minimized or covered.
+// on demand, shaders which do not read the time are only rendered again on input, reloads and resizes,
+// as every frame would be the same.
+
+use std::time::{Duration, Instant};
+
+use naga::{AddressSpace, Expression, GlobalVariable, Handle, TypeInner};
+use winit::event_loop::ControlFlow;
+
+use crate::project::{ChannelInput, Project};
+use crate::shadertoy::InfoMember;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PresentMode {
+    // waits for vsync
+    #[default]
+    Fifo,
+    // replaces the frame waiting for vsync, without tearing
+    Mailbox,
+    // tears
+    Immediate,
+}
+
+impl From<PresentMode> for wgpu::PresentMode {
+    fn from(present_mode: PresentMode) -> Self {
+        match present_mode {
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+}
+
+pub struct FramePacing {
+    pub present_mode: PresentMode,
+    // frames per second, none for no limit
+    max_fps: Option<f64>,
+    unfocused_fps: Option<f64>,
+    on_demand: bool,
+    focused: bool,
+    minimized: bool,
+    occluded: bool,
+    // something changed since the last frame
+    redraw: bool,
+    last_frame: Option<Instant>,
+}
+
+impl FramePacing {
+    // fps of 0 or below are no limit
+    pub fn new(
+        present_mode: PresentMode,
+        max_fps: Option<f64>,
+        unfocused_fps: f64,
+        on_demand: bool,
+    ) -> Self {
+        Self {
+            present_mode,
+            max_fps: max_fps.filter(|fps| *fps > 0.0),
+            unfocused_fps: Some(unfocused_fps).filter(|fps| *fps > 0.0),
+            on_demand,
+            focused: true,
+            minimized: false,
+            occluded: false,
+            redraw: true,
+            last_frame: None,
+        }
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn set_minimized(&mut self, minimized: bool) {
+        self.minimized = minimized;
+    }
+
+    pub fn set_occluded(&mut self, occluded: bool) {
+        self.occluded = occluded;
+    }
+
+    // render again even on demand
+    pub fn request_redraw(&mut self) {
+        self.redraw = true;
+    }
+
+    pub fn rendered(&mut self) {
+        self.redraw = false;
+        self.last_frame = Some(Instant::now());
+    }
+
+    // when to render next, none to wait for events. `animated` if the frames differ without input.
+    pub fn next_frame(&self, animated: bool) -> Option<Instant> {
+        if self.minimized || self.occluded {
+            return None;
+        }
+        if self.on_demand && !animated && !self.redraw {
+            return None;
+        }
+
+        let fps = if self.focused {
+            self.max_fps
+        } else {
+            match (self.max_fps, self.unfocused_fps) {
+                (Some(max_fps), Some(unfocused_fps)) => Some(max_fps.min(unfocused_fps)),
+                (max_fps, unfocused_fps) => max_fps.or(unfocused_fps),
+            }
+        };
+        match (fps, self.last_frame) {
+            (Some(fps), Some(last_frame)) => Some(last_frame + Duration::from_secs_f64(1.0 / fps)),
+            _ => Some(Instant::now()),
+        }
+    }
+
+    // for the event loop, from the start on: polling while a frame is due, and waiting otherwise
+    pub fn control_flow(&self, animated: bool) -> ControlFlow {
+        match self.next_frame(animated) {
+            None => ControlFlow::Wait,
+            Some(time) if time <= Instant::now() => ControlFlow::Poll,
+            Some(time) => ControlFlow::WaitUntil(time),
+        }
+    }
+}
+
+// whether the frames of the project change without input: a pass reads the time, or the previous
+// frame of itself or of a pass after it. passes which do not parse count as animated.
+pub fn is_animated(project: &Project, sources: &[String]) -> bool {
+    let passes = &project.manifest.passes;
+    let feedback = passes.iter().enumerate().any(|(index, pass)| {
+        pass.channels
+            .iter()
+            .flatten()
+            .any(|channel| match &channel.input {
+                ChannelInput::Buffer { pass } => passes
+                    .iter()
+                    .position(|other| &other.name == pass)
+                    .is_some_and(|other| other >= index),
+                ChannelInput::Image { .. } => false,
+            })
+    });
+
+    feedback
+        || (0..passes.len()).any(|index| {
+            project
+                .parse_pass(index, sources)
+                .map_or(true, |shader| uses_time(&shader.module))
+        })
+}
+
+// reads a member of `Info` which changes with the time, or reads `Info` in a way which is not
+// followed, like loading all of it to pass it to a function, or indexing it dynamically
+fn uses_time(module: &naga::Module) -> bool {
+    let is_info = |global: Handle<GlobalVariable>| {
+        let variable = &module.global_variables[global];
+        match variable.space {
+            AddressSpace::Uniform => variable
+                .binding
+                .as_ref()
+                .is_some_and(|binding| binding.group == 0 && binding.binding == 0),
+            AddressSpace::PushConstant => true,
+            _ => false,
+        }
+    };
+    // members are told apart by offset, like in `shadertoy`
+    let member_uses_time = |global: Handle<GlobalVariable>, index: u32| {
+        let ty = module.global_variables[global].ty;
+        match &module.types[ty].inner {
+            TypeInner::Struct { members, .. } => members
+                .get(index as usize)
+                .and_then(|member| InfoMember::from_offset(member.offset))
+                .is_none_or(InfoMember::changes_with_time),
+            _ => true,
+        }
+    };
+
+    let functions = module.functions.iter().map(|(_, function)| function).chain(
+        module
+            .entry_points
+            .iter()
+            .map(|entry_point| &entry_point.function),
+    );
+    for function in functions {
+        // uniforms are read by loads, the glsl frontend makes pointers to all members up front
+        for (_, expression) in function.expressions.iter() {
+            let Expression::Load { mut pointer } = *expression else {
+                continue;
+            };
+            // the member of the outermost struct, none for all of it or a dynamic index
+            let mut member = None;
+            loop {
+                match function.expressions[pointer] {
+                    Expression::AccessIndex { base, index } => {
+                        member = Some(index);
+                        pointer = base;
+                    }
+                    Expression::Access { base, .. } => {
+                        member = None;
+                        pointer = base;
+                    }
+                    Expression::GlobalVariable(global) => {
+                        if is_info(global)
+                            && member.is_none_or(|index| member_uses_time(global, index))
+                        {
+                            return true;
+                        }
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+    false
+}
@@ -0,0 +1,110 @@
+// timings of the last frames, for the hud.
+// the frame time is between the starts of two frames, the cpu time is recording and submitting
+// the passes, and the gpu time the passes took on the gpu, from timestamp queries. without them,
+// the gpu time is until the gpu finished the passes, waiting for it.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::gpu::hud::{Graph, GRAPH_SAMPLES};
+
+// frames averaged for the numbers
+const AVERAGED_FRAMES: usize = 30;
+
+#[derive(Default)]
+pub struct FrameStats {
+    // milliseconds, oldest first
+    frame_times: VecDeque<f32>,
+    cpu_times: VecDeque<f32>,
+    gpu_times: VecDeque<f32>,
+    last_frame: Option<Instant>,
+}
+
+impl FrameStats {
+    // call once per frame
+    pub fn record(&mut self, cpu_time: Duration, gpu_time: Option<Duration>) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame.replace(now) {
+            push(&mut self.frame_times, milliseconds(now - last_frame));
+        }
+        push(&mut self.cpu_times, milliseconds(cpu_time));
+        push(&mut self.gpu_times, gpu_time.map_or(0.0, milliseconds));
+    }
+
+    // frames around a pause of rendering, like a reload, are not measured as one
+    pub fn restart(&mut self) {
+        self.last_frame = None;
+    }
+
+    pub fn graph(&self) -> Graph<'_> {
+        Graph {
+            frame_times: self.frame_times.as_slices().0,
+            gpu_times: self.gpu_times.as_slices().0,
+        }
+    }
+
+    // `pass_times` are the names and milliseconds of the passes from timestamp queries, none
+    // without them
+    pub fn text(
+        &self,
+        size: [u32; 2],
+        adapter: &wgpu::AdapterInfo,
+        pass_times: Option<&[(&str, f64)]>,
+    ) -> String {
+        let frame_time = average(&self.frame_times);
+        let fps = if frame_time > 0.0 {
+            1000.0 / frame_time
+        } else {
+            0.0
+        };
+        let mut text = format!(
+            "FPS     {:>8.1}\n\
+             Frame   {:>8.2} ms\n\
+             CPU     {:>8.2} ms\n\
+             GPU     {:>8.2} ms {}\n",
+            fps,
+            frame_time,
+            average(&self.cpu_times),
+            average(&self.gpu_times),
+            if pass_times.is_some() {
+                "(timestamps)"
+            } else {
+                "(waited)"
+            },
+        );
+        // one pass is the whole gpu time
+        if let Some(pass_times) = pass_times.filter(|pass_times| pass_times.len() > 1) {
+            for (name, time) in pass_times {
+                text.push_str(&format!("  {:<6.6}{:>8.2} ms\n", name, time));
+            }
+        }
+        text.push_str(&format!(
+            "Size    {}x{}\n\
+             Adapter {}\n\
+             Backend {}",
+            size[0], size[1], adapter.name, adapter.backend,
+        ));
+        text
+    }
+}
+
+fn push(samples: &mut VecDeque<f32>, sample: f32) {
+    if samples.len() == GRAPH_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+    // one slice for the graph
+    samples.make_contiguous();
+}
+
+fn milliseconds(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+fn average(samples: &VecDeque<f32>) -> f32 {
+    let count = samples.len().min(AVERAGED_FRAMES);
+    if count == 0 {
+        return 0.0;
+    }
+    samples.iter().rev().take(count).sum::<f32>() / count as f32
+}
@@ -1,4 +1,15 @@
+pub mod adapter;
 pub mod gpu_state;
 pub use gpu_state::GpuState;
 pub mod renderer;
-pub use renderer::Renderer;
\ No newline at end of file
+pub use renderer::Renderer;
+pub mod gpu_timer;
+pub mod headless;
+pub mod hud;
+pub mod overlay;
+pub mod overlay_pipeline;
+pub mod readback;
+pub mod text;
+pub mod texture;
+pub mod timeline_bar;
+pub mod watchdog;
@@ -0,0 +1,215 @@
+// which gpu to render with: the backend, the power preference, or an adapter picked by index or name.
+// indices are the order adapters are listed in with `--list-adapters`, for the same backend.
+// with `software`, a cpu adapter is used, like wgpu's fallback adapter or mesa's llvmpipe through gl,
+// for machines without a gpu. what these do not support is left out, like msaa.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Backend {
+    Vulkan,
+    Gl,
+    Dx12,
+    Metal,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum PowerPreference {
+    Low,
+    #[default]
+    High,
+    None,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AdapterSettings {
+    // the primary backends if none: vulkan, metal, dx12 and webgpu
+    pub backend: Option<Backend>,
+    pub power_preference: PowerPreference,
+    // an index or part of a name, case insensitive
+    pub adapter: Option<String>,
+    pub software: bool,
+}
+
+impl AdapterSettings {
+    pub fn backends(&self) -> wgpu::Backends {
+        match self.backend {
+            // llvmpipe is a gl driver
+            None if self.software => wgpu::Backends::PRIMARY | wgpu::Backends::GL,
+            None => wgpu::Backends::PRIMARY,
+            Some(Backend::Vulkan) => wgpu::Backends::VULKAN,
+            Some(Backend::Gl) => wgpu::Backends::GL,
+            Some(Backend::Dx12) => wgpu::Backends::DX12,
+            Some(Backend::Metal) => wgpu::Backends::METAL,
+        }
+    }
+
+    pub fn create_instance(&self) -> wgpu::Instance {
+        wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: self.backends(),
+            ..Default::default()
+        })
+    }
+
+    // the adapter picked, the software one, or the best one for the power preference
+    pub async fn request_adapter(
+        &self,
+        instance: &wgpu::Instance,
+        surface: Option<&wgpu::Surface<'_>>,
+    ) -> Result<wgpu::Adapter, String> {
+        let Some(selection) = self.adapter.as_deref() else {
+            let options = |force_fallback_adapter| wgpu::RequestAdapterOptions {
+                power_preference: match self.power_preference {
+                    PowerPreference::Low => wgpu::PowerPreference::LowPower,
+                    PowerPreference::High => wgpu::PowerPreference::HighPerformance,
+                    PowerPreference::None => wgpu::PowerPreference::None,
+                },
+                compatible_surface: surface,
+                force_fallback_adapter,
+            };
+            if !self.software {
+                return instance.request_adapter(&options(false)).await.map_err(|e| {
+                    format!(
+                        "No GPU adapter found: {}\n\
+                         without a GPU, `--software` renders on the CPU, see also `--list-adapters`",
+                        e
+                    )
+                });
+            }
+
+            if let Ok(adapter) = instance.request_adapter(&options(true)).await {
+                return Ok(adapter);
+            }
+            return instance
+                .enumerate_adapters(self.backends())
+                .into_iter()
+                .find(|adapter| {
+                    adapter.get_info().device_type == wgpu::DeviceType::Cpu
+                        && surface.is_none_or(|surface| adapter.is_surface_supported(surface))
+                })
+                .ok_or_else(|| {
+                    "No software adapter found\n\
+                     install a CPU driver like Mesa's llvmpipe, see also `--list-adapters`"
+                        .to_string()
+                });
+        };
+
+        let mut adapters = instance.enumerate_adapters(self.backends()).into_iter();
+        let adapter = match selection.parse::<usize>() {
+            Ok(index) => adapters.nth(index),
+            Err(_) => {
+                let selection = selection.to_lowercase();
+                adapters.find(|adapter| adapter.get_info().name.to_lowercase().contains(&selection))
+            }
+        }
+        .ok_or_else(|| {
+            format!(
+                "No GPU adapter matches `{}`, see `--list-adapters`",
+                selection
+            )
+        })?;
+
+        if surface.is_some_and(|surface| !adapter.is_surface_supported(surface)) {
+            return Err(format!(
+                "The GPU adapter {} can not present to the window",
+                adapter.get_info().name
+            ));
+        }
+        Ok(adapter)
+    }
+}
+
+// like `llvmpipe (gl, Cpu)`, for telling what was selected
+pub fn describe(info: &wgpu::AdapterInfo) -> String {
+    let mut description = format!("{} ({}, {:?})", info.name, info.backend, info.device_type);
+    if info.device_type == wgpu::DeviceType::Cpu {
+        description.push_str(", software rendering, expect low frame rates");
+    }
+    description
+}
+
+// the timestamp queries for the gpu time of the passes, and the optional features of shaders,
+// where the adapter has them
+pub fn required_features(adapter: &wgpu::Adapter) -> wgpu::Features {
+    adapter.features() & (wgpu::Features::TIMESTAMP_QUERY | crate::features::all())
+}
+
+// the default limits, or the ones of older and software adapters if the adapter is below them
+pub fn required_limits(adapter: &wgpu::Adapter) -> wgpu::Limits {
+    let supported = adapter.limits();
+    let limits = [
+        wgpu::Limits::default(),
+        wgpu::Limits::downlevel_defaults(),
+        wgpu::Limits::downlevel_webgl2_defaults(),
+    ]
+    .into_iter()
+    .find(|limits| limits.check_limits(&supported))
+    .unwrap_or_else(wgpu::Limits::downlevel_webgl2_defaults)
+    .using_resolution(supported.clone());
+    wgpu::Limits {
+        // 0 without push constants
+        max_push_constant_size: supported.max_push_constant_size,
+        ..limits
+    }
+}
+
+// 4 for msaa where the format supports it, else 1
+pub fn sample_count(adapter: &wgpu::Adapter, format: wgpu::TextureFormat) -> u32 {
+    let flags = adapter.get_texture_format_features(format).flags;
+    if flags.sample_count_supported(4)
+        && flags.contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE)
+    {
+        4
+    } else {
+        1
+    }
+}
+
+// `--list-adapters`
+pub fn print_adapters(settings: &AdapterSettings) {
+    let adapters = settings
+        .create_instance()
+        .enumerate_adapters(settings.backends());
+    if adapters.is_empty() {
+        println!("No GPU adapters found");
+        return;
+    }
+
+    for (index, adapter) in adapters.iter().enumerate() {
+        let info = adapter.get_info();
+        println!("{}: {}", index, info.name);
+        println!("  Backend:     {}", info.backend);
+        println!("  Device type: {:?}", info.device_type);
+        println!("  Vendor:      {:#06x}", info.vendor);
+        println!("  Device:      {:#06x}", info.device);
+        println!("  Driver:      {} {}", info.driver, info.driver_info);
+        println!("  Features:");
+        let features = wrap(adapter.features().iter_names().map(|(name, _)| name), 96);
+        if features.is_empty() {
+            println!("    none");
+        }
+        for line in features {
+            println!("    {}", line);
+        }
+        println!("  Limits:");
+        // the fields of the debug output, one per line
+        let limits = format!("{:#?}", adapter.limits());
+        for line in limits.lines().filter(|line| line.starts_with("    ")) {
+            println!("    {}", line.trim().trim_end_matches(','));
+        }
+        println!();
+    }
+}
+
+// names joined into lines of at most `width` characters
+fn wrap<'a>(names: impl Iterator<Item = &'a str>, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for name in names {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + name.len() <= width => {
+                line.push(' ');
+                line.push_str(name);
+            }
+            _ => lines.push(name.to_string()),
+        }
+    }
+    lines
+}
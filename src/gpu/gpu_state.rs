@@ -1,9 +1,8 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
-const POWER_PREFERENCE: wgpu::PowerPreference = wgpu::PowerPreference::HighPerformance;
+use super::adapter::AdapterSettings;
 
 pub struct GpuState<'a> {
-    instance: wgpu::Instance,
     adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
@@ -11,57 +10,72 @@ pub struct GpuState<'a> {
     config: wgpu::SurfaceConfiguration,
     surface: wgpu::Surface<'a>,
     surface_format: wgpu::TextureFormat,
+    // 1 where the adapter has no msaa for the surface format
+    sample_count: u32,
     multisample_texture: wgpu::Texture,
+
+    // errors outside of error scopes, taken by the app after each frame
+    uncaptured_errors: Arc<Mutex<Vec<wgpu::Error>>>,
+    // set with the reason when the device is lost
+    lost: Arc<Mutex<Option<String>>>,
+    // of the last frame, so failing frames print it once
+    surface_error: Option<wgpu::SurfaceError>,
 }
 
 impl GpuState<'_> {
-    pub async fn new(winit_window: Arc<winit::window::Window>) -> Self {
+    pub async fn new(
+        winit_window: Arc<winit::window::Window>,
+        adapter_settings: &AdapterSettings,
+        present_mode: wgpu::PresentMode,
+    ) -> Result<Self, String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let instance = adapter_settings.create_instance();
+        #[cfg(target_arch = "wasm32")]
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            #[cfg(not(target_arch = "wasm32"))]
-            backends: wgpu::Backends::PRIMARY,
-            #[cfg(target_arch = "wasm32")]
             backends: wgpu::Backends::GL,
             ..Default::default()
         });
 
-        let surface = instance.create_surface(winit_window.clone()).unwrap();
+        let surface = instance
+            .create_surface(winit_window.clone())
+            .map_err(|e| format!("Error when creating the surface: {}", e))?;
 
-        let adapter = instance
-            .request_adapter(
-                &(wgpu::RequestAdapterOptions {
-                    power_preference: POWER_PREFERENCE,
-                    compatible_surface: Some(&surface),
-                    force_fallback_adapter: false,
-                }),
-            )
-            .await
-            .unwrap();
+        let adapter = adapter_settings
+            .request_adapter(&instance, Some(&surface))
+            .await?;
 
         let (device, queue) = adapter
             .request_device(
                 &(wgpu::DeviceDescriptor {
                     label: None,
-                    required_features: wgpu::Features::empty(),
+                    required_features: super::adapter::required_features(&adapter),
                     // WebGL doesn't support all of wgpu's features, so if
                     // we're building for the web, we'll have to disable some.
                     required_limits: if cfg!(target_arch = "wasm32") {
                         wgpu::Limits::downlevel_webgl2_defaults()
                     } else {
-                        wgpu::Limits::default()
+                        super::adapter::required_limits(&adapter)
                     },
                     memory_hints: wgpu::MemoryHints::default(),
                     trace: wgpu::Trace::Off,
                 })
             )
             .await
-            .unwrap();
+            .map_err(|e| format!("Error when requesting a device: {}", e))?;
 
         // set gpu error callback
-        device.on_uncaptured_error(Box::new(|e: wgpu::Error| {
-            eprintln!("! Uncaptured error:\n{}", e);
-            std::process::exit(1);
+        let uncaptured_errors = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::clone(&uncaptured_errors);
+        device.on_uncaptured_error(Box::new(move |e: wgpu::Error| {
+            errors.lock().unwrap().push(e);
         }));
 
+        let lost = Arc::new(Mutex::new(None));
+        let reason = Arc::clone(&lost);
+        device.set_device_lost_callback(move |kind, message| {
+            *reason.lock().unwrap() = Some(format!("{:?}: {}", kind, message));
+        });
+
         let surface_caps = surface.get_capabilities(&adapter);
         // Shader code in this tutorial assumes an sRGB surface texture. Using a different
         // one will result in all the colors coming out darker. If you want to support non
@@ -73,6 +87,19 @@ impl GpuState<'_> {
             .copied()
             .unwrap_or(surface_caps.formats[0]);
 
+        let sample_count = super::adapter::sample_count(&adapter, surface_format);
+
+        // fifo is always there
+        let present_mode = if surface_caps.present_modes.contains(&present_mode) {
+            present_mode
+        } else {
+            eprintln!(
+                "The surface does not support the {:?} present mode, using Fifo",
+                present_mode
+            );
+            wgpu::PresentMode::Fifo
+        };
+
         let size = winit_window.inner_size();
 
         let config = wgpu::SurfaceConfiguration {
@@ -80,7 +107,7 @@ impl GpuState<'_> {
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode,
             desired_maximum_frame_latency: 2,
             alpha_mode: wgpu::CompositeAlphaMode::Opaque,
             view_formats: vec![],
@@ -94,7 +121,7 @@ impl GpuState<'_> {
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
-            sample_count: 4,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: surface_format,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -103,16 +130,19 @@ impl GpuState<'_> {
 
         surface.configure(&device, &config);
 
-        Self {
-            instance,
+        Ok(Self {
             adapter,
             device,
             queue,
             config,
             surface,
             surface_format,
+            sample_count,
             multisample_texture,
-        }
+            uncaptured_errors,
+            lost,
+            surface_error: None,
+        })
     }
 
     pub fn get_device(&self) -> &wgpu::Device {
@@ -127,8 +157,50 @@ impl GpuState<'_> {
         self.surface_format
     }
 
-    pub fn get_current_texture(&self) -> wgpu::SurfaceTexture {
-        self.surface.get_current_texture().unwrap()
+    pub fn get_sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn get_adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter.get_info()
+    }
+
+    // none if there is no frame to render to this time
+    pub fn get_current_texture(&mut self) -> Option<wgpu::SurfaceTexture> {
+        let result = match self.surface.get_current_texture() {
+            Err(wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost) => {
+                // reconfigure and try once more
+                self.surface.configure(&self.device, &self.config);
+                self.surface.get_current_texture()
+            }
+            result => result,
+        };
+
+        match result {
+            Ok(surface_texture) => {
+                self.surface_error = None;
+                Some(surface_texture)
+            }
+            // skip the frame, it happens while the window is hidden
+            Err(wgpu::SurfaceError::Timeout) => None,
+            Err(e) => {
+                // printed when frames start failing, not for every frame
+                if self.surface_error.as_ref() != Some(&e) {
+                    eprintln!("\nError when getting the surface texture: {}", e);
+                }
+                self.surface_error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn take_uncaptured_errors(&self) -> Vec<wgpu::Error> {
+        std::mem::take(&mut *self.uncaptured_errors.lock().unwrap())
+    }
+
+    // the reason if the device is lost
+    pub fn lost_reason(&self) -> Option<String> {
+        self.lost.lock().unwrap().clone()
     }
 
     pub fn get_multisample_texture(&self) -> &wgpu::Texture {
@@ -139,10 +211,6 @@ impl GpuState<'_> {
         &self.config
     }
 
-    pub fn get_viewport_size(&self) -> [f32; 2] {
-        [self.config.width as f32, self.config.height as f32]
-    }
-
     pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
         if size.width > 0 && size.height > 0 {
             // Update the surface configuration
@@ -159,7 +227,7 @@ impl GpuState<'_> {
                     depth_or_array_layers: 1,
                 },
                 mip_level_count: 1,
-                sample_count: 4,
+                sample_count: self.sample_count,
                 dimension: wgpu::TextureDimension::D2,
                 format: self.config.format,
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
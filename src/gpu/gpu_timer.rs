@@ -0,0 +1,235 @@
+// how long the gpu spends on each pass, with timestamp queries written at the beginning and the end
+// of the passes. the timestamps of a frame are copied into a readback buffer which is mapped
+// without waiting, and read a frame or two later. a frame finding no free buffer is not timed.
+// only on adapters with `TIMESTAMP_QUERY`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+// passes timed in a frame, the ones after are not
+pub const MAX_PASSES: usize = 16;
+const READBACK_BUFFERS: usize = 4;
+const BUFFER_SIZE: u64 = (MAX_PASSES * 2 * std::mem::size_of::<u64>()) as u64;
+
+struct Readback {
+    buffer: wgpu::Buffer,
+    passes: usize,
+    // of `GpuTimer::generation` when the frame was timed
+    generation: u64,
+    // set by the map callback, whether mapping worked
+    mapped: Arc<Mutex<Option<bool>>>,
+}
+
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    free: Vec<wgpu::Buffer>,
+    // resolved and being mapped, oldest first
+    pending: VecDeque<Readback>,
+    // the buffer the frame being recorded resolves into
+    current: Option<Readback>,
+    // nanoseconds per timestamp tick
+    period: f64,
+    // counts restarts, readbacks of frames before the latest one are dropped
+    generation: u64,
+}
+
+impl GpuTimer {
+    // none if the device does not support timestamp queries
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Timestamp Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: (MAX_PASSES * 2) as u32,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size: BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let free = (0..READBACK_BUFFERS)
+            .map(|_| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Timestamp Readback Buffer"),
+                    size: BUFFER_SIZE,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            free,
+            pending: VecDeque::new(),
+            current: None,
+            period: queue.get_timestamp_period() as f64,
+            generation: 0,
+        })
+    }
+
+    // start timing a frame of this many passes, if a buffer is free for it
+    pub fn begin_frame(&mut self, passes: usize) {
+        self.current = self.free.pop().map(|buffer| Readback {
+            buffer,
+            passes: passes.min(MAX_PASSES),
+            generation: self.generation,
+            mapped: Arc::new(Mutex::new(None)),
+        });
+    }
+
+    // for the render pass of this index in the frame begun last
+    pub fn timestamp_writes(&self, pass: usize) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        (self.current.is_some() && pass < MAX_PASSES).then(|| wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(pass as u32 * 2),
+            end_of_pass_write_index: Some(pass as u32 * 2 + 1),
+        })
+    }
+
+    // after the passes of the frame are recorded
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(current) = self.current.as_ref() else {
+            return;
+        };
+        let queries = current.passes as u32 * 2;
+        encoder.resolve_query_set(&self.query_set, 0..queries, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &current.buffer,
+            0,
+            queries as u64 * std::mem::size_of::<u64>() as u64,
+        );
+    }
+
+    // after the frame is submitted
+    pub fn submitted(&mut self) {
+        let Some(current) = self.current.take() else {
+            return;
+        };
+        let mapped = Arc::clone(&current.mapped);
+        current
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *mapped.lock().unwrap() = Some(result.is_ok());
+            });
+        self.pending.push_back(current);
+    }
+
+    // frames submitted before are not read back, like the frames of the code before a reload
+    pub fn restart(&mut self) {
+        self.generation += 1;
+    }
+
+    // milliseconds of each pass of the latest frame read back since the last call.
+    // the map callbacks run when the device is polled.
+    pub fn take_pass_times(&mut self) -> Option<Vec<f64>> {
+        let mut latest = None;
+        while let Some(readback) = self.pending.front() {
+            let Some(mapped) = *readback.mapped.lock().unwrap() else {
+                break;
+            };
+            let readback = self.pending.pop_front().unwrap();
+
+            if mapped {
+                if readback.generation == self.generation {
+                    let data = readback.buffer.slice(..).get_mapped_range();
+                    let timestamps: &[u64] = bytemuck::cast_slice(&data);
+                    latest = Some(
+                        timestamps[..readback.passes * 2]
+                            .chunks_exact(2)
+                            .map(|pair| {
+                                pair[1].saturating_sub(pair[0]) as f64 * self.period / 1_000_000.0
+                            })
+                            .collect(),
+                    );
+                }
+                readback.buffer.unmap();
+            }
+            self.free.push(readback.buffer);
+        }
+        latest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gpu::adapter::AdapterSettings;
+    use crate::gpu::headless::Headless;
+
+    // one timed pass clearing a small texture
+    fn time_frame(timer: &mut GpuTimer, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        timer.begin_frame(1);
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: timer.timestamp_writes(0),
+            occlusion_query_set: None,
+        });
+        timer.resolve(&mut encoder);
+        queue.submit(Some(encoder.finish()));
+        timer.submitted();
+        device.poll(wgpu::PollType::Wait).unwrap();
+    }
+
+    #[test]
+    fn restart_drops_pending_readbacks_and_unmaps_them() {
+        let settings = AdapterSettings {
+            software: true,
+            ..Default::default()
+        };
+        let Ok(gpu) = pollster::block_on(Headless::new(&settings)) else {
+            eprintln!("no adapter, skipped");
+            return;
+        };
+        let Some(mut timer) = GpuTimer::new(&gpu.device, &gpu.queue) else {
+            eprintln!("no timestamp queries, skipped");
+            return;
+        };
+
+        time_frame(&mut timer, &gpu.device, &gpu.queue);
+        timer.restart();
+        assert_eq!(timer.take_pass_times(), None);
+
+        // the buffer is free again, copying into it would fail if it were still mapped
+        gpu.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        time_frame(&mut timer, &gpu.device, &gpu.queue);
+        let error = pollster::block_on(gpu.device.pop_error_scope());
+        assert!(error.is_none(), "{:?}", error);
+        assert_eq!(timer.take_pass_times().map(|times| times.len()), Some(1));
+    }
+}
@@ -0,0 +1,33 @@
+// a device without a window or surface, for rendering offscreen
+
+use super::adapter::AdapterSettings;
+
+pub struct Headless {
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+}
+
+impl Headless {
+    pub async fn new(adapter_settings: &AdapterSettings) -> Result<Self, String> {
+        let instance = adapter_settings.create_instance();
+        let adapter = adapter_settings.request_adapter(&instance, None).await?;
+
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("Headless Device"),
+                required_features: super::adapter::required_features(&adapter),
+                required_limits: super::adapter::required_limits(&adapter),
+                memory_hints: wgpu::MemoryHints::default(),
+                trace: wgpu::Trace::Off,
+            })
+            .await
+            .map_err(|e| format!("Error when requesting a device: {}", e))?;
+
+        Ok(Self {
+            adapter,
+            device,
+            queue,
+        })
+    }
+}
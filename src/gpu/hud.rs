@@ -0,0 +1,104 @@
+// draws the performance numbers in the top right corner of the window, over a graph of the
+// time of the last frames. the text is drawn with the font of the overlay.
+
+use super::overlay_pipeline::OverlayPipeline;
+use super::text;
+
+// frames in the graph
+pub const GRAPH_SAMPLES: usize = 256;
+const MAX_COLUMNS: usize = 64;
+const MAX_LINES: usize = 16;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct HudInfo {
+    window_size: [f32; 2],
+    text_size: [f32; 2],
+    scale: f32,
+    graph_max: f32,
+    _padding: [f32; 2],
+    frame_times: [f32; GRAPH_SAMPLES],
+    gpu_times: [f32; GRAPH_SAMPLES],
+}
+
+// milliseconds of the frames, oldest first
+pub struct Graph<'a> {
+    pub frame_times: &'a [f32],
+    pub gpu_times: &'a [f32],
+}
+
+pub struct Hud {
+    pipeline: OverlayPipeline,
+    text: Option<(wgpu::BindGroup, [u32; 2])>,
+}
+
+impl Hud {
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        Self {
+            pipeline: OverlayPipeline::new::<HudInfo>(
+                device,
+                surface_format,
+                "Hud",
+                wgpu::include_wgsl!("hud.wgsl"),
+                true,
+            ),
+            text: None,
+        }
+    }
+
+    pub fn set_text(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, text: &str) {
+        let (view, size) = text::create_texture(device, queue, text, MAX_COLUMNS, MAX_LINES);
+        let binding_group = self.pipeline.create_binding_group(device, Some(&view));
+        self.text = Some((binding_group, size));
+    }
+
+    // draw over what is already in the view, nothing before the first `set_text`
+    pub fn render(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        window_size: [f32; 2],
+        graph: &Graph,
+    ) {
+        let Some((binding_group, text_size)) = self.text.as_ref() else {
+            return;
+        };
+
+        // the newest frame at the right end
+        let mut frame_times = [0.0; GRAPH_SAMPLES];
+        let mut gpu_times = [0.0; GRAPH_SAMPLES];
+        for (sample, time) in frame_times
+            .iter_mut()
+            .rev()
+            .zip(graph.frame_times.iter().rev())
+        {
+            *sample = *time;
+        }
+        for (sample, time) in gpu_times.iter_mut().rev().zip(graph.gpu_times.iter().rev()) {
+            *sample = *time;
+        }
+        // 30 fps fits, and longer frames stretch the graph
+        let graph_max = frame_times
+            .iter()
+            .copied()
+            .fold(1000.0 / 30.0 * 1.2, f32::max);
+
+        let scale = (window_size[1] / 600.0).round().max(1.0);
+        self.pipeline.draw(
+            queue,
+            encoder,
+            view,
+            binding_group,
+            &HudInfo {
+                window_size,
+                text_size: [text_size[0] as f32 * scale, text_size[1] as f32 * scale],
+                scale,
+                graph_max,
+                _padding: [0.0; 2],
+                frame_times,
+                gpu_times,
+            },
+        );
+    }
+}
@@ -0,0 +1,65 @@
+struct Hud {
+    window_size: vec2<f32>,
+    // size of the text in pixels of the window
+    text_size: vec2<f32>,
+    scale: f32,
+    // milliseconds at the top of the graph
+    graph_max: f32,
+    // four per element, oldest first, the newest at the right of the graph
+    frame_times: array<vec4<f32>, 64>,
+    gpu_times: array<vec4<f32>, 64>,
+};
+
+@group(0) @binding(0) var<uniform> hud: Hud;
+// one texel per pixel of the font, 1 where a glyph is drawn
+@group(0) @binding(1) var text: texture_2d<f32>;
+
+const margin: f32 = 16.0;
+const padding: f32 = 8.0;
+const samples: f32 = 256.0;
+const graph_height: f32 = 64.0;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // in the top right corner, the text over the graph
+    let graph_size = vec2<f32>(samples, graph_height) * hud.scale;
+    let content_size = vec2<f32>(
+        max(hud.text_size.x, graph_size.x),
+        hud.text_size.y + padding + graph_size.y,
+    );
+    let panel_size = content_size + vec2<f32>(padding * 2.0);
+    let panel = position.xy - vec2<f32>(hud.window_size.x - margin - panel_size.x, margin);
+    if any(panel < vec2<f32>(0.0)) || any(panel >= panel_size) {
+        discard;
+    }
+    let content = panel - vec2<f32>(padding);
+
+    let texel = vec2<i32>(floor(content / hud.scale));
+    let in_text = all(texel >= vec2<i32>(0)) && all(texel < vec2<i32>(textureDimensions(text)));
+    if in_text && textureLoad(text, texel, 0).r > 0.5 {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+
+    let graph = content - vec2<f32>(0.0, hud.text_size.y + padding);
+    if all(graph >= vec2<f32>(0.0)) && all(graph < graph_size) {
+        let index = u32(graph.x / hud.scale);
+        let frame_time = hud.frame_times[index / 4u][index % 4u];
+        let gpu_time = hud.gpu_times[index / 4u][index % 4u];
+        // milliseconds at the pixel, and per pixel
+        let time = (graph_size.y - graph.y) / graph_size.y * hud.graph_max;
+        let pixel_time = hud.graph_max / graph_size.y;
+
+        // 60 and 30 fps
+        if abs(time - 1000.0 / 60.0) < pixel_time * 0.5 || abs(time - 1000.0 / 30.0) < pixel_time * 0.5 {
+            return vec4<f32>(0.5, 0.5, 0.5, 1.0);
+        }
+        if time <= gpu_time {
+            return vec4<f32>(1.0, 0.6, 0.2, 1.0);
+        }
+        if time <= frame_time {
+            return vec4<f32>(0.3, 0.8, 0.4, 1.0);
+        }
+        return vec4<f32>(0.1, 0.1, 0.1, 0.8);
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 0.8);
+}
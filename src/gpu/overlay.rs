@@ -0,0 +1,83 @@
+// draws the errors of the current code on top of the shader output, with a colored border
+// telling that the window still shows the last working shader.
+// the text is rasterized on the cpu with an 8x8 bitmap font, so no font file is needed.
+
+use super::overlay_pipeline::OverlayPipeline;
+use super::text;
+
+// longer lines are wrapped, and lines beyond the last one are cut
+const MAX_COLUMNS: usize = 120;
+const MAX_LINES: usize = 40;
+const BORDER_WIDTH: f32 = 4.0;
+const BORDER_COLOR: [f32; 4] = [0.9, 0.15, 0.1, 1.0];
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct OverlayInfo {
+    border_color: [f32; 4],
+    window_size: [f32; 2],
+    text_size: [f32; 2],
+    scale: f32,
+    border_width: f32,
+    _padding: [f32; 2],
+}
+
+pub struct Overlay {
+    pipeline: OverlayPipeline,
+    // none while there is no error to show
+    text: Option<(wgpu::BindGroup, [u32; 2])>,
+}
+
+impl Overlay {
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        Self {
+            pipeline: OverlayPipeline::new::<OverlayInfo>(
+                device,
+                surface_format,
+                "Overlay",
+                wgpu::include_wgsl!("overlay.wgsl"),
+                true,
+            ),
+            text: None,
+        }
+    }
+
+    // show the text, or hide the overlay with `None`
+    pub fn set_text(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, text: Option<&str>) {
+        self.text = text.map(|text| {
+            let (view, size) = text::create_texture(device, queue, text, MAX_COLUMNS, MAX_LINES);
+            let binding_group = self.pipeline.create_binding_group(device, Some(&view));
+            (binding_group, size)
+        });
+    }
+
+    // draw over what is already in the view
+    pub fn render(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        window_size: [f32; 2],
+    ) {
+        let Some((binding_group, text_size)) = self.text.as_ref() else {
+            return;
+        };
+
+        // bigger glyphs on high resolution windows
+        let scale = (window_size[1] / 600.0).round().max(1.0);
+        self.pipeline.draw(
+            queue,
+            encoder,
+            view,
+            binding_group,
+            &OverlayInfo {
+                border_color: BORDER_COLOR,
+                window_size,
+                text_size: [text_size[0] as f32 * scale, text_size[1] as f32 * scale],
+                scale,
+                border_width: BORDER_WIDTH,
+                _padding: [0.0; 2],
+            },
+        );
+    }
+}
@@ -0,0 +1,36 @@
+struct Overlay {
+    border_color: vec4<f32>,
+    window_size: vec2<f32>,
+    // size of the text in pixels of the window, before clipping
+    text_size: vec2<f32>,
+    scale: f32,
+    border_width: f32,
+};
+
+@group(0) @binding(0) var<uniform> overlay: Overlay;
+// one texel per pixel of the font, 1 where a glyph is drawn
+@group(0) @binding(1) var text: texture_2d<f32>;
+
+const margin: f32 = 16.0;
+const padding: f32 = 8.0;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let distance_to_edge = min(position.xy, overlay.window_size - position.xy);
+    if min(distance_to_edge.x, distance_to_edge.y) < overlay.border_width {
+        return overlay.border_color;
+    }
+
+    let panel = position.xy - vec2<f32>(margin);
+    let panel_size = overlay.text_size + vec2<f32>(padding * 2.0);
+    if any(panel < vec2<f32>(0.0)) || any(panel >= panel_size) {
+        discard;
+    }
+
+    let texel = vec2<i32>(floor((panel - vec2<f32>(padding)) / overlay.scale));
+    let in_text = all(texel >= vec2<i32>(0)) && all(texel < vec2<i32>(textureDimensions(text)));
+    if in_text && textureLoad(text, texel, 0).r > 0.5 {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 0.8);
+}
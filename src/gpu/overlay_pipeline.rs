@@ -0,0 +1,154 @@
+// the pipeline of what is drawn over the shader output, like the error overlay, the hud and the
+// timeline: the full screen quad of `vertex_pass_through.wgsl`, a fragment shader blended on top,
+// a uniform buffer at binding 0, and the texture of `text::create_texture` at binding 1 for text.
+
+pub struct OverlayPipeline {
+    pipeline: wgpu::RenderPipeline,
+    binding_group_layout: wgpu::BindGroupLayout,
+    info_buffer: wgpu::Buffer,
+    name: &'static str,
+}
+
+impl OverlayPipeline {
+    // `Info` is the uniform of the fragment shader
+    pub fn new<Info: bytemuck::Pod>(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        name: &'static str,
+        fragment: wgpu::ShaderModuleDescriptor,
+        text: bool,
+    ) -> Self {
+        let mut entries = vec![wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }];
+        if text {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            });
+        }
+        let binding_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(&format!("{} Binding Group Layout", name)),
+                entries: &entries,
+            });
+
+        let info_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{} Info Buffer", name)),
+            size: std::mem::size_of::<Info>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{} Pipeline Layout", name)),
+            bind_group_layouts: &[&binding_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let v_shader = device.create_shader_module(wgpu::include_wgsl!("vertex_pass_through.wgsl"));
+        let f_shader = device.create_shader_module(fragment);
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("{} Pipeline", name)),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &v_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &f_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            binding_group_layout,
+            info_buffer,
+            name,
+        }
+    }
+
+    // the info buffer, and the text where the pipeline has it
+    pub fn create_binding_group(
+        &self,
+        device: &wgpu::Device,
+        text: Option<&wgpu::TextureView>,
+    ) -> wgpu::BindGroup {
+        let mut entries = vec![wgpu::BindGroupEntry {
+            binding: 0,
+            resource: self.info_buffer.as_entire_binding(),
+        }];
+        if let Some(text) = text {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(text),
+            });
+        }
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{} Binding Group", self.name)),
+            layout: &self.binding_group_layout,
+            entries: &entries,
+        })
+    }
+
+    // draw over what is already in the view, with the frame's other passes in `encoder`
+    pub fn draw<Info: bytemuck::Pod>(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        binding_group: &wgpu::BindGroup,
+        info: &Info,
+    ) {
+        queue.write_buffer(&self.info_buffer, 0, bytemuck::bytes_of(info));
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(&format!("{} Pass", self.name)),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, binding_group, &[]);
+        render_pass.draw(0..4, 0..1);
+    }
+}
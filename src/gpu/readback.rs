@@ -0,0 +1,64 @@
+// copy a texture back to the cpu.
+// only `Rgba32Float` is supported, which is what `Renderer::render_float_target` produces.
+pub fn read_rgba32f(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Vec<[f32; 4]> {
+    assert_eq!(texture.format(), wgpu::TextureFormat::Rgba32Float);
+
+    let width = texture.width();
+    let height = texture.height();
+
+    // rows of a texture copy must be aligned
+    let unpadded_bytes_per_row = width * std::mem::size_of::<[f32; 4]>() as u32;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size: (padded_bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| {});
+    device.poll(wgpu::PollType::Wait).unwrap();
+
+    let data = slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for row in data.chunks_exact(padded_bytes_per_row as usize) {
+        pixels.extend_from_slice(bytemuck::cast_slice::<u8, [f32; 4]>(
+            &row[..unpadded_bytes_per_row as usize],
+        ));
+    }
+    drop(data);
+    buffer.unmap();
+
+    pixels
+}
@@ -2,33 +2,87 @@ use std::sync::Arc;
 
 use wgpu::util::DeviceExt;
 
+use super::gpu_timer::GpuTimer;
+
+pub const FLOAT_TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+// passes other than the last one render into these, they are filterable without extra features
+pub const BUFFER_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const CHANNEL_COUNT: usize = 4;
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct ViewportInfo {
     pub size: [f32; 2],
     pub time_from_start_up: f32,
     pub time_from_update: f32,
+    // xy: cursor position while the left button is held, zw: where it was pressed.
+    // zw is negative while the button is released, like shadertoy's `iMouse`, but top left origin.
+    pub mouse: [f32; 4],
+    // frames rendered since the last reload, and how long the previous frame took
+    pub frame: u32,
+    pub time_delta: f32,
+    pub _padding: [u32; 2],
+}
+
+// what a pass reads through one of its channels
+#[derive(Clone, Debug)]
+pub enum ChannelSource {
+    // the output of another pass by index, or of the pass itself from the previous frame
+    Buffer(usize),
+    Texture(wgpu::TextureView),
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub source: ChannelSource,
+    pub sampler: wgpu::Sampler,
+}
+
+pub struct PassDescriptor<'a> {
+    pub source: wgpu::ShaderSource<'a>,
+    pub channels: [Option<Channel>; CHANNEL_COUNT],
+}
+
+struct Pass {
+    f_shader: wgpu::ShaderModule,
+    pipeline: wgpu::RenderPipeline,
+    channels: [Option<Channel>; CHANNEL_COUNT],
+}
+
+// the output of a pass which is not the last one.
+// the pass writes one texture while the other one keeps the previous frame, then they are swapped.
+struct Buffer {
+    textures: [wgpu::Texture; 2],
+    current: usize,
 }
 
 pub struct Renderer {
     // format
     surface_format: wgpu::TextureFormat,
+    // of the last pass, 1 renders to the surface directly
+    sample_count: u32,
 
     // binding group
     binding_group_layout: wgpu::BindGroupLayout,
+    // `Info` is also in push constants, where the device has them
+    push_constants: bool,
     viewport_info_buffer: wgpu::Buffer,
-    binding_group: wgpu::BindGroup,
+    // bound to channels without input
+    empty_channel: wgpu::TextureView,
+    empty_sampler: wgpu::Sampler,
 
     // about shaders
     v_shader: wgpu::ShaderModule,
-    f_shader: Arc<wgpu::ShaderModule>,
-    last_working_f_shader: Option<Arc<wgpu::ShaderModule>>,
+    // the last pass renders to the surface, the others to `buffers`
+    passes: Arc<Vec<Pass>>,
+    last_working_passes: Option<Arc<Vec<Pass>>>,
     is_f_shader_ensured: bool, // this is to ensure that the fragment shader is not broken
 
     // pipeline
     pipeline_layout: wgpu::PipelineLayout,
-    pipeline: Arc<wgpu::RenderPipeline>,
-    last_working_pipeline: Option<Arc<wgpu::RenderPipeline>>,
+
+    // outputs of the passes
+    buffers: Vec<Buffer>,
 }
 
 impl Renderer {
@@ -36,21 +90,43 @@ impl Renderer {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
         f_shader: &str,
     ) -> Self {
+        // 0: `Info`, then the texture and the sampler of each channel
+        let mut layout_entries = vec![wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }];
+        for channel in 0..CHANNEL_COUNT as u32 {
+            layout_entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 1 + channel * 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            });
+            layout_entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 2 + channel * 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            });
+        }
+
         let binding_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Binding Group Layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
+                entries: &layout_entries,
             });
 
         let viewport_info_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -59,23 +135,52 @@ impl Renderer {
                 size: [800.0, 600.0],
                 time_from_start_up: 0.0,
                 time_from_update: 0.0,
+                mouse: [0.0; 4],
+                frame: 0,
+                time_delta: 0.0,
+                _padding: [0; 2],
             }]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let binding_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Binding Group"),
-            layout: &binding_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: viewport_info_buffer.as_entire_binding(),
-            }],
-        });
-
+        let empty_channel = device
+            .create_texture_with_data(
+                queue,
+                &wgpu::TextureDescriptor {
+                    label: Some("Empty Channel"),
+                    size: wgpu::Extent3d {
+                        width: 1,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+                wgpu::util::TextureDataOrder::LayerMajor,
+                &[0, 0, 0, 0],
+            )
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let empty_sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
+
+        let info_size = std::mem::size_of::<ViewportInfo>() as u32;
+        let push_constants = device.features().contains(wgpu::Features::PUSH_CONSTANTS)
+            && device.limits().max_push_constant_size >= info_size;
+        let push_constant_ranges = if push_constants {
+            vec![wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::FRAGMENT,
+                range: 0..info_size,
+            }]
+        } else {
+            Vec::new()
+        };
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pipeline Layout"),
             bind_group_layouts: &[&binding_group_layout],
-            push_constant_ranges: &[],
+            push_constant_ranges: &push_constant_ranges,
         });
 
         let v_shader = device.create_shader_module(wgpu::include_wgsl!("vertex_pass_through.wgsl"));
@@ -85,128 +190,95 @@ impl Renderer {
             source: wgpu::ShaderSource::Wgsl(f_shader.into()),
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &v_shader,
-                entry_point: Some("vs_main"),
-                buffers: &[],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &f_shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            // depth_stencil: None,
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 4,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
+        let pipeline = create_render_pipeline(
+            device,
+            &pipeline_layout,
+            &v_shader,
+            &f_shader,
+            surface_format,
+            sample_count,
+        );
 
         Self {
             surface_format,
+            sample_count,
             binding_group_layout,
+            push_constants,
             viewport_info_buffer,
-            binding_group,
+            empty_channel,
+            empty_sampler,
             v_shader,
-            f_shader: Arc::new(f_shader),
-            last_working_f_shader: None,
+            passes: Arc::new(vec![Pass {
+                f_shader,
+                pipeline,
+                channels: Default::default(),
+            }]),
+            last_working_passes: None,
             is_f_shader_ensured: true,
             pipeline_layout,
-            pipeline: Arc::new(pipeline),
-            last_working_pipeline: None,
+            buffers: Vec::new(),
         }
     }
 
+    // replace all passes at once. on error, the index of the pass which failed is returned.
     pub async fn update_fragment(
         &mut self,
-        f_shader_code: &str,
+        passes: Vec<PassDescriptor<'_>>,
         device: &wgpu::Device,
-    ) -> Result<(), wgpu::Error> {
-        // swap the shader and pipeline to the last working one
-        self.last_working_f_shader = Some(Arc::clone(&self.f_shader));
-        self.last_working_pipeline = Some(Arc::clone(&self.pipeline));
-
-        // change this to true if this function successfully ends.
-        self.is_f_shader_ensured = false;
-
-        // Create a new fragment shader module
-        let f_shader_new = with_validation_error_handling(device, || {
-            device.create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: Some("Fragment Shader"),
-                source: wgpu::ShaderSource::Wgsl(f_shader_code.into()),
+    ) -> Result<(), (usize, wgpu::Error)> {
+        let last = passes.len().saturating_sub(1);
+        let mut new_passes = Vec::with_capacity(passes.len());
+
+        for (index, pass) in passes.into_iter().enumerate() {
+            // Create a new fragment shader module
+            let f_shader = with_validation_error_handling(device, || {
+                device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Fragment Shader"),
+                    source: pass.source,
+                })
             })
-        })
-        .await?;
+            .await
+            .map_err(|e| (index, e))?;
 
-        // Update the fragment shader
-        self.f_shader = Arc::new(f_shader_new);
-
-        // Update the render pipeline with the new fragment shader
-        let new_pipeline = with_validation_error_handling(device, || {
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&self.pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &self.v_shader,
-                    entry_point: Some("vs_main"),
-                    buffers: &[],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &self.f_shader,
-                    entry_point: Some("fs_main"),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: self.surface_format,
-                        blend: Some(wgpu::BlendState::REPLACE),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleStrip,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    unclipped_depth: false,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 4,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
+            let (format, sample_count) = if index == last {
+                (self.surface_format, self.sample_count)
+            } else {
+                (BUFFER_FORMAT, 1)
+            };
+
+            // Create the render pipeline with the new fragment shader
+            let pipeline = with_validation_error_handling(device, || {
+                create_render_pipeline(
+                    device,
+                    &self.pipeline_layout,
+                    &self.v_shader,
+                    &f_shader,
+                    format,
+                    sample_count,
+                )
             })
-        })
-        .await?;
+            .await
+            .map_err(|e| (index, e))?;
+
+            new_passes.push(Pass {
+                f_shader,
+                pipeline,
+                channels: pass.channels,
+            });
+        }
 
-        // Update the pipeline
-        self.pipeline = Arc::new(new_pipeline);
+        // keep the passes on screen until the new ones rendered once.
+        // if the current ones never did, the last working ones stay.
+        if self.is_f_shader_ensured {
+            self.last_working_passes = Some(Arc::clone(&self.passes));
+        }
+        self.passes = Arc::new(new_passes);
+
+        // change this to true if the new passes successfully render.
+        self.is_f_shader_ensured = false;
+
+        // like shadertoy, buffers start empty with new code
+        self.buffers.clear();
 
         Ok(())
     }
@@ -220,13 +292,15 @@ impl Renderer {
         viewport_info: ViewportInfo
     ) -> Result<(), wgpu::Error> {
         with_validation_error_handling(device, || {
-            self.render(
+            let encoder = self.render(
                 device,
                 queue,
                 surface_view,
                 multi_sample_view,
                 viewport_info,
+                None,
             );
+            queue.submit(std::iter::once(encoder.finish()));
         })
         .await?;
 
@@ -236,14 +310,17 @@ impl Renderer {
         Ok(())
     }
 
+    // the encoder with the passes of a frame, to draw over and submit.
+    // with a timer, call `GpuTimer::submitted` once it is submitted.
     pub fn render(
-        &self,
+        &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         surface_view: &wgpu::TextureView,
         multi_sample_view: &wgpu::TextureView,
         viewport_info: ViewportInfo,
-    ) {
+        mut timer: Option<&mut GpuTimer>,
+    ) -> wgpu::CommandEncoder {
         // Update the viewport info buffer
         queue.write_buffer(
             &self.viewport_info_buffer,
@@ -251,17 +328,236 @@ impl Renderer {
             bytemuck::cast_slice(&[viewport_info]),
         );
 
+        let passes = Arc::clone(self.active_passes());
+        self.prepare_buffers(device, passes.len() - 1, viewport_info.size);
+        if let Some(timer) = timer.as_mut() {
+            timer.begin_frame(passes.len());
+        }
+
         // render
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
 
-        {
+        for (index, pass) in passes.iter().enumerate() {
+            // created before this pass swaps its buffer, so it reads its own previous frame,
+            // and the current frame of the passes before it
+            let binding_group = self.create_binding_group(device, pass);
+
+            let buffer_view;
+            let color_attachment = match self.buffers.get_mut(index) {
+                Some(buffer) => {
+                    buffer.current = 1 - buffer.current;
+                    buffer_view = buffer.textures[buffer.current]
+                        .create_view(&wgpu::TextureViewDescriptor::default());
+                    wgpu::RenderPassColorAttachment {
+                        view: &buffer_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }
+                }
+                None if self.sample_count == 1 => wgpu::RenderPassColorAttachment {
+                    view: surface_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                },
+                None => wgpu::RenderPassColorAttachment {
+                    view: multi_sample_view,
+                    resolve_target: Some(surface_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                },
+            };
+
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
+                color_attachments: &[Some(color_attachment)],
+                depth_stencil_attachment: None,
+                timestamp_writes: timer
+                    .as_ref()
+                    .and_then(|timer| timer.timestamp_writes(index)),
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&pass.pipeline);
+            render_pass.set_bind_group(0, &binding_group, &[]);
+            if self.push_constants {
+                render_pass.set_push_constants(
+                    wgpu::ShaderStages::FRAGMENT,
+                    0,
+                    bytemuck::bytes_of(&viewport_info),
+                );
+            }
+            render_pass.draw(0..4, 0..1);
+        }
+
+        if let Some(timer) = timer {
+            timer.resolve(&mut encoder);
+        }
+        encoder
+    }
+
+    // go back to the last passes which rendered, after the current ones failed outside of `try_render`.
+    // false if there are none to go back to.
+    pub fn revert(&mut self) -> bool {
+        let Some(passes) = self.last_working_passes.take() else {
+            return false;
+        };
+        self.passes = passes;
+        self.is_f_shader_ensured = true;
+        self.buffers.clear();
+
+        true
+    }
+
+    fn active_passes(&self) -> &Arc<Vec<Pass>> {
+        if self.is_f_shader_ensured {
+            &self.passes
+        } else {
+            self.last_working_passes.as_ref().unwrap()
+        }
+    }
+
+    // make a buffer of the viewport size for each pass but the last one, discarding their content on change
+    fn prepare_buffers(&mut self, device: &wgpu::Device, count: usize, size: [f32; 2]) {
+        let size = wgpu::Extent3d {
+            width: (size[0] as u32).max(1),
+            height: (size[1] as u32).max(1),
+            depth_or_array_layers: 1,
+        };
+
+        if self.buffers.len() == count
+            && self
+                .buffers
+                .first()
+                .is_none_or(|buffer| buffer.textures[0].size() == size)
+        {
+            return;
+        }
+
+        let create_texture = || {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Pass Buffer"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: BUFFER_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+        };
+
+        self.buffers = (0..count)
+            .map(|_| Buffer {
+                textures: [create_texture(), create_texture()],
+                current: 0,
+            })
+            .collect();
+    }
+
+    fn create_binding_group(&self, device: &wgpu::Device, pass: &Pass) -> wgpu::BindGroup {
+        let views = pass.channels.iter().map(|channel| {
+            match channel.as_ref().map(|channel| &channel.source) {
+                Some(ChannelSource::Texture(view)) => view.clone(),
+                Some(ChannelSource::Buffer(index)) => match self.buffers.get(*index) {
+                    Some(buffer) => buffer.textures[buffer.current]
+                        .create_view(&wgpu::TextureViewDescriptor::default()),
+                    None => self.empty_channel.clone(),
+                },
+                None => self.empty_channel.clone(),
+            }
+        });
+        let views: Vec<_> = views.collect();
+
+        let mut entries = vec![wgpu::BindGroupEntry {
+            binding: 0,
+            resource: self.viewport_info_buffer.as_entire_binding(),
+        }];
+        for (index, (view, channel)) in views.iter().zip(pass.channels.iter()).enumerate() {
+            let sampler = channel
+                .as_ref()
+                .map_or(&self.empty_sampler, |channel| &channel.sampler);
+            entries.push(wgpu::BindGroupEntry {
+                binding: 1 + index as u32 * 2,
+                resource: wgpu::BindingResource::TextureView(view),
+            });
+            entries.push(wgpu::BindGroupEntry {
+                binding: 2 + index as u32 * 2,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            });
+        }
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Binding Group"),
+            layout: &self.binding_group_layout,
+            entries: &entries,
+        })
+    }
+
+    // render one frame into a new float texture with the shader currently on screen.
+    // this is used for exports, which need more precision than the surface has.
+    // only the last pass is rendered again, it reads the buffers as they are on screen.
+    pub fn render_float_target(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: [u32; 2],
+        viewport_info: ViewportInfo,
+    ) -> wgpu::Texture {
+        let pass = self.active_passes().last().unwrap();
+
+        let pipeline = create_render_pipeline(
+            device,
+            &self.pipeline_layout,
+            &self.v_shader,
+            &pass.f_shader,
+            FLOAT_TARGET_FORMAT,
+            1,
+        );
+        let binding_group = self.create_binding_group(device, pass);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Float Target"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FLOAT_TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        queue.write_buffer(
+            &self.viewport_info_buffer,
+            0,
+            bytemuck::cast_slice(&[viewport_info]),
+        );
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Float Target Encoder"),
+        });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Float Target Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: multi_sample_view,
-                    resolve_target: Some(surface_view),
+                    view: &view,
+                    resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
                         store: wgpu::StoreOp::Store,
@@ -272,19 +568,76 @@ impl Renderer {
                 occlusion_query_set: None,
             });
 
-            if self.is_f_shader_ensured {
-                render_pass.set_pipeline(self.pipeline.as_ref());
-            } else {
-                render_pass.set_pipeline(self.last_working_pipeline.as_ref().unwrap());
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &binding_group, &[]);
+            if self.push_constants {
+                render_pass.set_push_constants(
+                    wgpu::ShaderStages::FRAGMENT,
+                    0,
+                    bytemuck::bytes_of(&viewport_info),
+                );
             }
-            render_pass.set_bind_group(0, &self.binding_group, &[]);
             render_pass.draw(0..4, 0..1);
         }
 
         queue.submit(std::iter::once(encoder.finish()));
+
+        texture
     }
 }
 
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    v_shader: &wgpu::ShaderModule,
+    f_shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: v_shader,
+            entry_point: Some("vs_main"),
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: f_shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                // 32-bit float targets are not blendable without extra features
+                blend: format
+                    .guaranteed_format_features(device.features())
+                    .flags
+                    .contains(wgpu::TextureFormatFeatureFlags::BLENDABLE)
+                    .then_some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
 async fn with_validation_error_handling<T, F: FnOnce() -> T>(
     device: &wgpu::Device,
     f: F,
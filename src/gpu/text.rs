@@ -0,0 +1,87 @@
+// text drawn by the overlays, rasterized on the cpu with an 8x8 bitmap font.
+
+use wgpu::util::DeviceExt;
+
+// glyph size of the font, in texels
+pub const GLYPH_SIZE: u32 = 8;
+
+// an R8Unorm texture with the text, 1 where a glyph is drawn, and its size in texels.
+// longer lines are wrapped, and lines beyond the last one are cut.
+pub fn create_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    text: &str,
+    max_columns: usize,
+    max_lines: usize,
+) -> (wgpu::TextureView, [u32; 2]) {
+    let (pixels, size) = rasterize(text, max_columns, max_lines);
+    let view = device
+        .create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Text"),
+                size: wgpu::Extent3d {
+                    width: size[0],
+                    height: size[1],
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &pixels,
+        )
+        .create_view(&wgpu::TextureViewDescriptor::default());
+    (view, size)
+}
+
+// one byte per texel, 255 where a glyph is drawn, and the size in texels
+fn rasterize(text: &str, max_columns: usize, max_lines: usize) -> (Vec<u8>, [u32; 2]) {
+    let lines: Vec<Vec<u8>> = text
+        .lines()
+        .flat_map(|line| {
+            // the font only has ascii
+            let line: Vec<u8> = line
+                .chars()
+                .map(|c| match c {
+                    '\t' => b' ',
+                    c if c.is_ascii() => c as u8,
+                    _ => b'?',
+                })
+                .collect();
+            if line.is_empty() {
+                vec![Vec::new()]
+            } else {
+                line.chunks(max_columns).map(<[u8]>::to_vec).collect()
+            }
+        })
+        .take(max_lines)
+        .collect();
+
+    let columns = lines.iter().map(Vec::len).max().unwrap_or(0).max(1) as u32;
+    let width = columns * GLYPH_SIZE;
+    let height = lines.len().max(1) as u32 * GLYPH_SIZE;
+
+    let mut pixels = vec![0; (width * height) as usize];
+    for (row, line) in lines.iter().enumerate() {
+        for (column, c) in line.iter().enumerate() {
+            let glyph = font8x8::legacy::BASIC_LEGACY[*c as usize];
+            for (y, bits) in glyph.iter().enumerate() {
+                for x in 0..GLYPH_SIZE as usize {
+                    // the lowest bit is the leftmost texel
+                    if bits & (1 << x) != 0 {
+                        let px = column * GLYPH_SIZE as usize + x;
+                        let py = row * GLYPH_SIZE as usize + y;
+                        pixels[py * width as usize + px] = 255;
+                    }
+                }
+            }
+        }
+    }
+
+    (pixels, [width, height])
+}
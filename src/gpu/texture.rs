@@ -0,0 +1,58 @@
+use wgpu::util::DeviceExt;
+
+// load an image file for a channel.
+// `vflip` puts the first row of the file at the bottom, where shadertoy has it by default.
+pub fn load_image(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    path: &std::path::Path,
+    vflip: bool,
+    srgb: bool,
+) -> Result<wgpu::TextureView, image::ImageError> {
+    let mut image = image::open(path)?.to_rgba8();
+    if vflip {
+        image::imageops::flip_vertical_in_place(&mut image);
+    }
+
+    let texture = device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("Channel Image"),
+            size: wgpu::Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: if srgb {
+                wgpu::TextureFormat::Rgba8UnormSrgb
+            } else {
+                wgpu::TextureFormat::Rgba8Unorm
+            },
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        image.as_raw(),
+    );
+
+    Ok(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
+pub fn create_sampler(
+    device: &wgpu::Device,
+    filter: wgpu::FilterMode,
+    address_mode: wgpu::AddressMode,
+) -> wgpu::Sampler {
+    device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Channel Sampler"),
+        address_mode_u: address_mode,
+        address_mode_v: address_mode,
+        address_mode_w: address_mode,
+        mag_filter: filter,
+        min_filter: filter,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,102 @@
+// draws the timeline at the bottom of the window: the time, the loop region, bookmarks and
+// tick marks, over the seconds from 0 to `duration`.
+
+use super::overlay_pipeline::OverlayPipeline;
+
+pub const MAX_BOOKMARKS: usize = 16;
+// in pixels at a scale of 1
+const HEIGHT: f32 = 16.0;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct BarInfo {
+    window_size: [f32; 2],
+    height: f32,
+    duration: f32,
+    time: f32,
+    loop_in: f32,
+    loop_out: f32,
+    tick: f32,
+    bookmark_count: u32,
+    _padding: [u32; 3],
+    bookmarks: [f32; MAX_BOOKMARKS],
+}
+
+// what the bar shows, in seconds
+pub struct BarState<'a> {
+    pub duration: f64,
+    pub time: f64,
+    pub loop_region: Option<(f64, f64)>,
+    pub bookmarks: &'a [f64],
+}
+
+pub struct TimelineBar {
+    pipeline: OverlayPipeline,
+    binding_group: wgpu::BindGroup,
+}
+
+// of the bar in a window of this size, bigger on high resolution windows like the overlay
+pub fn height(window_size: [f32; 2]) -> f32 {
+    HEIGHT * (window_size[1] / 600.0).round().max(1.0)
+}
+
+impl TimelineBar {
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let pipeline = OverlayPipeline::new::<BarInfo>(
+            device,
+            surface_format,
+            "Timeline",
+            wgpu::include_wgsl!("timeline_bar.wgsl"),
+            false,
+        );
+        let binding_group = pipeline.create_binding_group(device, None);
+        Self {
+            pipeline,
+            binding_group,
+        }
+    }
+
+    // draw over what is already in the view
+    pub fn render(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        window_size: [f32; 2],
+        state: &BarState,
+    ) {
+        let (loop_in, loop_out) = state.loop_region.unwrap_or((-1.0, -1.0));
+        let mut bookmarks = [0.0; MAX_BOOKMARKS];
+        for (bookmark, time) in bookmarks.iter_mut().zip(state.bookmarks) {
+            *bookmark = *time as f32;
+        }
+
+        self.pipeline.draw(
+            queue,
+            encoder,
+            view,
+            &self.binding_group,
+            &BarInfo {
+                window_size,
+                height: height(window_size),
+                duration: state.duration as f32,
+                time: state.time as f32,
+                loop_in: loop_in as f32,
+                loop_out: loop_out as f32,
+                tick: tick(state.duration, window_size[0]) as f32,
+                bookmark_count: state.bookmarks.len().min(MAX_BOOKMARKS) as u32,
+                _padding: [0; 3],
+                bookmarks,
+            },
+        );
+    }
+}
+
+// the shortest of these steps which puts tick marks at least 8 pixels apart, 0 for none
+fn tick(duration: f64, width: f32) -> f64 {
+    let min_seconds = duration * 8.0 / width.max(1.0) as f64;
+    [1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 600.0, 1800.0, 3600.0]
+        .into_iter()
+        .find(|seconds| *seconds >= min_seconds)
+        .unwrap_or(0.0)
+}
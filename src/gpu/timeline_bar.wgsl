@@ -0,0 +1,60 @@
+struct Bar {
+    window_size: vec2<f32>,
+    height: f32,
+    // seconds from the left to the right end
+    duration: f32,
+    time: f32,
+    // negative without a loop region
+    loop_in: f32,
+    loop_out: f32,
+    // seconds between tick marks
+    tick: f32,
+    bookmark_count: u32,
+    // four per element
+    bookmarks: array<vec4<f32>, 4>,
+};
+
+@group(0) @binding(0) var<uniform> bar: Bar;
+
+// whether the pixel is within `width` pixels of `time`
+fn is_at(time: f32, x: f32, width: f32) -> bool {
+    let pixels_per_second = bar.window_size.x / bar.duration;
+    return abs(x - time) * pixels_per_second < width;
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let top = bar.window_size.y - bar.height;
+    if position.y < top {
+        discard;
+    }
+    // seconds at the pixel, and how far down the bar it is from 0 to 1
+    let x = position.x / bar.window_size.x * bar.duration;
+    let y = (position.y - top) / bar.height;
+
+    if is_at(bar.time, x, 1.0) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    for (var i = 0u; i < bar.bookmark_count; i++) {
+        if is_at(bar.bookmarks[i / 4u][i % 4u], x, 1.0) {
+            return vec4<f32>(1.0, 0.8, 0.2, 1.0);
+        }
+    }
+
+    let has_loop = bar.loop_in >= 0.0 && bar.loop_out > bar.loop_in;
+    if has_loop && (is_at(bar.loop_in, x, 1.0) || is_at(bar.loop_out, x, 1.0)) {
+        return vec4<f32>(0.4, 0.7, 1.0, 1.0);
+    }
+    if bar.tick > 0.0 && y > 0.6 && is_at(round(x / bar.tick) * bar.tick, x, 0.5) {
+        return vec4<f32>(0.6, 0.6, 0.6, 0.9);
+    }
+
+    var color = vec4<f32>(0.05, 0.05, 0.05, 0.75);
+    if has_loop && x >= bar.loop_in && x <= bar.loop_out {
+        color = vec4<f32>(0.15, 0.3, 0.55, 0.8);
+    }
+    if x <= bar.time {
+        color = vec4<f32>(color.rgb + vec3<f32>(0.1), color.a);
+    }
+    return color;
+}
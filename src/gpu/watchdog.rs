@@ -0,0 +1,70 @@
+// notice frames which the gpu does not finish in time, for `--safe`.
+// a thread polls the device, since the main thread may itself be stuck waiting for the gpu.
+// when a frame is late, the device is destroyed to get the gpu back, and the app starts over
+// on a new device as it does when the device is lost.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub struct Watchdog {
+    // when each submitted frame which is not finished yet was submitted, oldest first
+    pending: Arc<Mutex<VecDeque<Instant>>>,
+    triggered: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    pub fn new(device: wgpu::Device, timeout: Duration) -> Self {
+        let pending = Arc::new(Mutex::new(VecDeque::<Instant>::new()));
+        let triggered = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let watched = Arc::clone(&pending);
+        let trigger = Arc::clone(&triggered);
+        let stopped = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(timeout / 8);
+
+                // run the callbacks of finished frames
+                device.poll(wgpu::PollType::Poll).ok();
+
+                let oldest = watched.lock().unwrap().front().copied();
+                if oldest.is_some_and(|submitted| submitted.elapsed() > timeout) {
+                    trigger.store(true, Ordering::Relaxed);
+                    device.destroy();
+                    return;
+                }
+            }
+        });
+
+        Self {
+            pending,
+            triggered,
+            stop,
+        }
+    }
+
+    // call after the work of a frame is submitted
+    pub fn submitted(&self, queue: &wgpu::Queue) {
+        self.pending.lock().unwrap().push_back(Instant::now());
+
+        let pending = Arc::clone(&self.pending);
+        queue.on_submitted_work_done(move || {
+            pending.lock().unwrap().pop_front();
+        });
+    }
+
+    // whether a frame was late and the device destroyed
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,304 @@
+// warnings for code which is valid but likely a mistake, found in the module naga parsed.
+// spans are in the module's source, items without a span are not reported.
+// names starting with `_` are taken as meant to be unused.
+
+use std::collections::{HashMap, HashSet};
+
+use naga::valid::{FunctionInfo, ModuleInfo};
+use naga::{
+    Arena, BinaryOperator, Block, Expression, Function, Handle, Literal, MathFunction, ScalarKind,
+    Span, Statement,
+};
+
+pub struct Lint {
+    pub span: Span,
+    pub message: String,
+}
+
+pub fn lint(module: &naga::Module, info: &ModuleInfo) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let mut called = HashSet::new();
+    let mut used_globals = HashSet::new();
+
+    let functions = module
+        .functions
+        .iter()
+        .map(|(handle, function)| (function, &info[handle]))
+        .chain(
+            module
+                .entry_points
+                .iter()
+                .enumerate()
+                .map(|(index, entry_point)| (&entry_point.function, info.get_entry_point(index))),
+        );
+    for (function, function_info) in functions {
+        lint_function(module, function, function_info, &mut called, &mut lints);
+
+        for (handle, _) in module.global_variables.iter() {
+            if !function_info[handle].is_empty() {
+                used_globals.insert(handle);
+            }
+        }
+    }
+
+    for (handle, function) in module.functions.iter() {
+        if let Some(name) = reported_name(&function.name) {
+            if !called.contains(&handle) {
+                lints.push(Lint {
+                    span: module.functions.get_span(handle),
+                    message: format!("function `{}` is never called", name),
+                });
+            }
+        }
+    }
+
+    for (handle, global) in module.global_variables.iter() {
+        if let Some(name) = reported_name(&global.name) {
+            if global.binding.is_some() && !used_globals.contains(&handle) {
+                lints.push(Lint {
+                    span: module.global_variables.get_span(handle),
+                    message: format!("`{}` is bound but never read", name),
+                });
+            }
+        }
+    }
+
+    // macros and inlined code can report the same place more than once
+    let mut seen = HashSet::new();
+    lints.retain(|lint| {
+        lint.span.is_defined() && seen.insert((lint.span.to_range(), lint.message.clone()))
+    });
+    lints.sort_by_key(|lint| lint.span.to_range().map(|range| range.start));
+
+    lints
+}
+
+fn lint_function(
+    module: &naga::Module,
+    function: &Function,
+    info: &FunctionInfo,
+    called: &mut HashSet<Handle<Function>>,
+    lints: &mut Vec<Lint>,
+) {
+    let mut stores = HashMap::new();
+    walk(&function.body, called, &mut stores);
+
+    // a variable is read if its pointer is used by anything but a store to it
+    for (handle, variable) in function.local_variables.iter() {
+        let Some(name) = reported_name(&variable.name) else {
+            continue;
+        };
+        let pointers = function.expressions.iter().filter(
+            |(_, expression)| matches!(expression, Expression::LocalVariable(local) if *local == handle),
+        );
+        let (uses, stored) = pointers.fold((0, 0), |(uses, stored), (pointer, _)| {
+            (
+                uses + info[pointer].ref_count,
+                stored + stores.get(&pointer).copied().unwrap_or(0),
+            )
+        });
+
+        if uses == stored {
+            lints.push(Lint {
+                span: function.local_variables.get_span(handle),
+                message: if stored == 0 {
+                    format!("variable `{}` is never used", name)
+                } else {
+                    format!("variable `{}` is assigned but never read", name)
+                },
+            });
+        }
+    }
+
+    for (index, argument) in function.arguments.iter().enumerate() {
+        let Some(name) = reported_name(&argument.name) else {
+            continue;
+        };
+        let references: Vec<_> = function
+            .expressions
+            .iter()
+            .filter(|(_, expression)| {
+                matches!(expression, Expression::FunctionArgument(i) if *i as usize == index)
+            })
+            .map(|(handle, _)| handle)
+            .collect();
+        if let Some(&first) = references.first() {
+            if references.iter().all(|handle| info[*handle].ref_count == 0) {
+                lints.push(Lint {
+                    span: function.expressions.get_span(first),
+                    message: format!("parameter `{}` is never used", name),
+                });
+            }
+        }
+    }
+
+    for (handle, expression) in function.expressions.iter() {
+        // left over by constant evaluation
+        if info[handle].ref_count == 0 {
+            continue;
+        }
+
+        let message = match *expression {
+            Expression::Binary {
+                op: BinaryOperator::Divide | BinaryOperator::Modulo,
+                right,
+                ..
+            } if has_zero(module, &function.expressions, right) => {
+                "division by a constant which is or has a zero".to_string()
+            }
+            Expression::Math {
+                fun: MathFunction::Pow,
+                arg,
+                ..
+            } if !is_non_negative(module, &function.expressions, arg) => {
+                "the base of `pow` may be negative, which gives NaN, `abs` or `max` it".to_string()
+            }
+            Expression::Binary {
+                op: op @ (BinaryOperator::Equal | BinaryOperator::NotEqual),
+                left,
+                ..
+            } if info[left].ty.inner_with(&module.types).scalar_kind()
+                == Some(ScalarKind::Float) =>
+            {
+                format!(
+                    "floats compared with `{}`, which rarely holds after arithmetic, compare with a tolerance",
+                    if op == BinaryOperator::Equal { "==" } else { "!=" }
+                )
+            }
+            _ => continue,
+        };
+
+        lints.push(Lint {
+            span: span(&function.expressions, handle),
+            message,
+        });
+    }
+}
+
+// naga's glsl frontend leaves calls of builtin functions without span, they get the span of their arguments
+fn span(expressions: &Arena<Expression>, handle: Handle<Expression>) -> Span {
+    let span = expressions.get_span(handle);
+    match expressions[handle] {
+        Expression::Math { arg, arg1, .. } if !span.is_defined() => Span::total_span(
+            std::iter::once(arg)
+                .chain(arg1)
+                .map(|arg| self::span(expressions, arg)),
+        ),
+        _ => span,
+    }
+}
+
+// collect the called functions, and how many times each pointer is stored to
+fn walk(
+    block: &Block,
+    called: &mut HashSet<Handle<Function>>,
+    stores: &mut HashMap<Handle<Expression>, usize>,
+) {
+    for statement in block.iter() {
+        match statement {
+            Statement::Block(block) => walk(block, called, stores),
+            Statement::If { accept, reject, .. } => {
+                walk(accept, called, stores);
+                walk(reject, called, stores);
+            }
+            Statement::Switch { cases, .. } => {
+                for case in cases {
+                    walk(&case.body, called, stores);
+                }
+            }
+            Statement::Loop {
+                body, continuing, ..
+            } => {
+                walk(body, called, stores);
+                walk(continuing, called, stores);
+            }
+            Statement::Call { function, .. } => {
+                called.insert(*function);
+            }
+            Statement::Store { pointer, .. } => {
+                *stores.entry(*pointer).or_default() += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn reported_name(name: &Option<String>) -> Option<&str> {
+    name.as_deref().filter(|name| !name.starts_with('_'))
+}
+
+// whether a constant is zero, or a vector with a zero component
+fn has_zero(
+    module: &naga::Module,
+    expressions: &Arena<Expression>,
+    handle: Handle<Expression>,
+) -> bool {
+    match expressions[handle] {
+        Expression::Literal(literal) => literal_value(literal) == Some(0.0),
+        Expression::ZeroValue(_) => true,
+        Expression::Constant(constant) => has_zero(
+            module,
+            &module.global_expressions,
+            module.constants[constant].init,
+        ),
+        Expression::Splat { value, .. } => has_zero(module, expressions, value),
+        Expression::Compose { ref components, .. } => components
+            .iter()
+            .any(|component| has_zero(module, expressions, *component)),
+        _ => false,
+    }
+}
+
+// whether a value is known to be zero or more
+fn is_non_negative(
+    module: &naga::Module,
+    expressions: &Arena<Expression>,
+    handle: Handle<Expression>,
+) -> bool {
+    let non_negative = |handle| is_non_negative(module, expressions, handle);
+    match expressions[handle] {
+        Expression::Literal(literal) => literal_value(literal).is_some_and(|value| value >= 0.0),
+        Expression::ZeroValue(_) => true,
+        Expression::Constant(constant) => is_non_negative(
+            module,
+            &module.global_expressions,
+            module.constants[constant].init,
+        ),
+        Expression::Splat { value, .. } => non_negative(value),
+        Expression::Compose { ref components, .. } => {
+            components.iter().all(|component| non_negative(*component))
+        }
+        Expression::Math { fun, arg, arg1, .. } => match fun {
+            MathFunction::Abs
+            | MathFunction::Exp
+            | MathFunction::Exp2
+            | MathFunction::Sqrt
+            | MathFunction::Length
+            | MathFunction::Distance
+            | MathFunction::Saturate
+            | MathFunction::SmoothStep => true,
+            MathFunction::Max => non_negative(arg) || arg1.is_some_and(non_negative),
+            MathFunction::Clamp => arg1.is_some_and(non_negative),
+            MathFunction::Dot => Some(arg) == arg1,
+            _ => false,
+        },
+        Expression::Binary {
+            op: BinaryOperator::Add | BinaryOperator::Multiply | BinaryOperator::Divide,
+            left,
+            right,
+        } => non_negative(left) && non_negative(right),
+        _ => false,
+    }
+}
+
+fn literal_value(literal: Literal) -> Option<f64> {
+    match literal {
+        Literal::F64(value) | Literal::AbstractFloat(value) => Some(value),
+        Literal::F32(value) => Some(value as f64),
+        Literal::U32(value) => Some(value as f64),
+        Literal::I32(value) => Some(value as f64),
+        Literal::U64(value) => Some(value as f64),
+        Literal::I64(value) | Literal::AbstractInt(value) => Some(value as f64),
+        _ => None,
+    }
+}
@@ -1,17 +1,40 @@
 use std::time::SystemTime;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use export::{ExportEncoding, ExportFormat, ExportSettings};
 use window::App;
-use winit::event_loop::{ControlFlow, EventLoop};
+use winit::event_loop::EventLoop;
 
+mod bench;
+mod check;
+mod clock;
+mod complexity;
+mod cross;
+mod diagnostics;
+mod export;
+mod export_html;
+mod features;
+mod frame_pacing;
+mod frame_stats;
 mod gpu;
+mod lint;
 mod poll_new_fragment_code;
+mod portability;
+mod project;
+mod safety;
+mod shader;
+mod shadertoy;
+mod timeline;
+mod version_stats;
 mod window;
 
 // args parsing
 #[derive(Parser)]
 #[command(name = "live-wgsl", version = "0.1.0")]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Shader file, or project directory / manifest
     #[arg(short, long, default_value = "live.wgsl")]
     path: String,
     // #[arg(short, long, default_value = "true")]
@@ -20,49 +43,342 @@ struct Args {
     interval: u64,
     // #[arg(short, long, default_value = "false")]
     // code: bool,
+    #[arg(long, value_enum, default_value = "png8")]
+    export_format: ExportFormat,
+    #[arg(long, value_enum)]
+    export_encoding: Option<ExportEncoding>,
+    /// Write the shader translated to GLSL, HLSL, MSL and SPIR-V next to it on every reload
+    #[arg(long)]
+    cross_compile: bool,
+    /// Check the shader against WebGPU, WebGL2, GLES, GL, Vulkan, Metal and DX12 on every reload
+    #[arg(long)]
+    check_portability: bool,
+    /// Keep the output of earlier compiles instead of clearing the terminal on every reload
+    #[arg(long)]
+    no_clear: bool,
+    /// Write the result of every compile to this file as a JSON line, truncated at start, for editor integration
+    #[arg(long, value_name = "FILE")]
+    diagnostics_json: Option<String>,
+    /// Write errors as `file:line:column: error: message` to a `.quickfix` file next to the shader
+    #[arg(long)]
+    quickfix: bool,
+    /// Cap every loop of the shader, and go back to the last working shader when a frame hangs the GPU
+    #[arg(long)]
+    safe: bool,
+    /// Iterations per loop entry before `--safe` breaks out of the loop
+    #[arg(long, default_value = "100000")]
+    loop_limit: u32,
+    /// Milliseconds a frame can take before `--safe` reverts the shader
+    #[arg(long, default_value = "2000")]
+    watchdog: u64,
+    /// Seconds the clocks of the shader start at
+    #[arg(long, default_value = "0")]
+    time: f64,
+    /// Playback speed of the clocks, negative runs them back
+    #[arg(long, default_value = "1", allow_negative_numbers = true)]
+    speed: f64,
+    /// Start with the clocks paused
+    #[arg(long)]
+    paused: bool,
+    /// Seconds a frame step with the arrow keys moves the clocks
+    #[arg(long, default_value_t = 1.0 / 60.0)]
+    step: f64,
+    /// Show the timeline at the bottom of the window
+    #[arg(long)]
+    timeline: bool,
+    /// Seconds the timeline spans, it grows by this when the time goes beyond
+    #[arg(long, default_value = "30")]
+    timeline_length: f64,
+    /// Show the performance numbers and frame time graph in the window
+    #[arg(long)]
+    hud: bool,
+    /// Graphics API to render with. Default is the primary one of the platform
+    #[arg(long, value_enum, global = true)]
+    backend: Option<gpu::adapter::Backend>,
+    /// Prefer the integrated (low) or the discrete (high) GPU
+    #[arg(long, value_enum, default_value = "high", global = true)]
+    power_preference: gpu::adapter::PowerPreference,
+    /// GPU adapter to render with, by index or part of its name, as listed by `--list-adapters`
+    #[arg(long, global = true)]
+    adapter: Option<String>,
+    /// Render on the CPU, with wgpu's fallback adapter or a software driver like llvmpipe
+    #[arg(long, global = true)]
+    software: bool,
+    /// Print the GPU adapters with their features and limits, and exit
+    #[arg(long)]
+    list_adapters: bool,
+    /// Versions of the code whose GPU time is kept, shown with `V`
+    #[arg(long, default_value = "10")]
+    version_history: usize,
+    /// How frames are shown: fifo waits for vsync, mailbox replaces the waiting frame, immediate tears
+    #[arg(long, value_enum, default_value = "fifo")]
+    present_mode: frame_pacing::PresentMode,
+    /// Frames per second at most
+    #[arg(long)]
+    max_fps: Option<f64>,
+    /// Frames per second at most while the window is not focused, 0 for no limit
+    #[arg(long, default_value = "10")]
+    unfocused_fps: f64,
+    /// Render only on input, reloads and resizes while the shader does not use the time
+    #[arg(long)]
+    on_demand: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render a shader or project offscreen and report statistics of its GPU time
+    Bench {
+        path: String,
+        /// Size of the frames, like `1920x1080`
+        #[arg(long, default_value = "1920x1080", value_parser = bench::parse_size)]
+        size: [u32; 2],
+        /// Frames measured
+        #[arg(long, default_value = "500")]
+        frames: u32,
+        /// Frames rendered before measuring, which are discarded
+        #[arg(long, default_value = "50")]
+        warm_up: u32,
+        /// Print the result as a JSON line
+        #[arg(long)]
+        json: bool,
+    },
+    /// Validate shaders and projects without a GPU, failing if any has errors
+    Check {
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Print the result of each path as a JSON line
+        #[arg(long)]
+        json: bool,
+    },
+    /// Translate a shader to GLSL, HLSL, MSL and SPIR-V
+    Cross {
+        path: String,
+        /// Print the translations instead of writing them next to the shader
+        #[arg(long)]
+        print: bool,
+    },
+    /// Write a self-contained WebGPU page running the shader
+    ExportHtml {
+        path: String,
+        /// Output file. Default is the shader path with `.html` extension
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Convert a Shadertoy GLSL shader (`.glsl` / `.frag`) to WGSL
+    Convert {
+        path: String,
+        /// Output file. Default is the shader path with `.wgsl` extension
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Import a Shadertoy JSON export into a project directory and start live editing it
+    Import {
+        path: String,
+        /// Project directory. Default is the JSON path without extension
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Only write the project
+        #[arg(long)]
+        no_run: bool,
+    },
+    /// Translate a shader to GLSL that can be pasted into Shadertoy
+    ExportShadertoy {
+        path: String,
+        /// Output file. Default is the shader path with `.shadertoy.glsl` extension
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Print the translation instead of writing it
+        #[arg(long)]
+        print: bool,
+    },
+}
+
+fn run_command(
+    command: Command,
+    adapter_settings: gpu::adapter::AdapterSettings,
+) -> std::process::ExitCode {
+    match command {
+        Command::Bench {
+            path,
+            size,
+            frames,
+            warm_up,
+            json,
+        } => bench::run(
+            &path,
+            &bench::BenchSettings {
+                size,
+                frames,
+                warm_up,
+                json,
+                adapter: adapter_settings,
+            },
+        ),
+        Command::Check { paths, json } => check::run(&paths, json),
+        Command::Cross { path, print } => cross::run(&path, print),
+        Command::ExportHtml { path, output } => export_html::run(&path, output.as_deref()),
+        Command::Convert { path, output } => shadertoy::import::run(&path, output.as_deref()),
+        Command::Import { path, output, .. } => shadertoy::json::run(&path, output.as_deref()),
+        Command::ExportShadertoy {
+            path,
+            output,
+            print,
+        } => shadertoy::export::run(&path, output.as_deref(), print),
+    }
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> std::process::ExitCode {
+    let args = Args::parse();
+
+    let adapter_settings = gpu::adapter::AdapterSettings {
+        backend: args.backend,
+        power_preference: args.power_preference,
+        adapter: args.adapter.clone(),
+        software: args.software,
+    };
+    if args.list_adapters {
+        gpu::adapter::print_adapters(&adapter_settings);
+        return std::process::ExitCode::SUCCESS;
+    }
+
+    let file_path = match args.command {
+        // go on with live editing the imported project
+        Some(Command::Import {
+            path,
+            output,
+            no_run: false,
+        }) => match shadertoy::json::import(&path, output.as_deref()) {
+            Some(project_path) => project_path.to_string_lossy().into_owned(),
+            None => return std::process::ExitCode::FAILURE,
+        },
+        Some(command) => return run_command(command, adapter_settings),
+        None => args.path,
+    };
+
     // assets
     let default_fragment_code = include_str!("./fragment_default.wgsl");
+    let default_shadertoy_code = include_str!("./fragment_default.glsl");
 
-    // build event loop
-    let event_loop: EventLoop<(Option<SystemTime>, String)> =
+    let project = if project::Project::is_project_path(std::path::Path::new(&file_path)) {
+        match project::Project::load(std::path::Path::new(&file_path)) {
+            Ok(project) => project,
+            Err(e) => {
+                eprintln!("Error when loading project:\n{}", e);
+                return std::process::ExitCode::FAILURE;
+            }
+        }
+    } else {
+        // check if the file exists
+        if !std::path::Path::new(&file_path).exists() {
+            // copy from the default fragment code
+            let code = match shader::Language::from_path(std::path::Path::new(&file_path)) {
+                shader::Language::Wgsl => default_fragment_code,
+                shader::Language::ShadertoyGlsl => default_shadertoy_code,
+            };
+            std::fs::write(&file_path, code).unwrap();
+        }
+        project::Project::single(std::path::Path::new(&file_path))
+    };
+
+    // build event loop, the default shader is animated
+    let frame_pacing = frame_pacing::FramePacing::new(
+        args.present_mode,
+        args.max_fps,
+        args.unfocused_fps,
+        args.on_demand,
+    );
+    let event_loop: EventLoop<(Option<SystemTime>, Vec<String>)> =
         EventLoop::with_user_event().build().unwrap();
-    event_loop.set_control_flow(ControlFlow::Poll);
+    event_loop.set_control_flow(frame_pacing.control_flow(true));
     let proxy = event_loop.create_proxy();
 
     // spawn a task to poll for new fragment code
-    let args = Args::parse();
-
-    let file_path = args.path;
     let interval = args.interval;
+    let export_settings = ExportSettings {
+        format: args.export_format,
+        encoding: args.export_encoding,
+    };
+
+    // show info, again on top of every compile if the screen is cleared
 
-    // check if the file exists
-    if !std::path::Path::new(&file_path).exists() {
-        // copy from the default fragment code
-        std::fs::write(&file_path, default_fragment_code).unwrap();
+    let mut info = String::new();
+    info.push('\n');
+    info.push_str("Edit shader file with your favorite editor!\n");
+    if !project.is_single() {
+        info.push_str(&format!("Project: {}\n", project.manifest.name));
     }
+    for file in project.files() {
+        info.push_str(&format!(
+            "Shader file: {}\n",
+            std::fs::canonicalize(&file).unwrap().display()
+        ));
+    }
+    info.push('\n');
+    info.push_str(&format!("Polling interval: {} ms\n\n", interval));
+    info.push_str("-------------------------------\n\n");
+    print!("{}", info);
 
-    // show info
+    let file_paths: Vec<String> = project
+        .files()
+        .iter()
+        .map(|file| file.to_string_lossy().into_owned())
+        .collect();
 
-    println!();
-    println!("Edit shader file with your favorite editor!");
-    println!(
-        "Shader file: {}",
-        std::fs::canonicalize(&file_path).unwrap().display()
+    let diagnostics_json = match args.diagnostics_json.as_deref() {
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!("Error when creating file: {}\n{}", path, e);
+                return std::process::ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    let quickfix = args
+        .quickfix
+        .then(|| project.image_path().with_extension("quickfix"));
+    let diagnostics = diagnostics::Diagnostics::new(
+        !args.no_clear,
+        info,
+        diagnostics_json,
+        quickfix,
+        file_paths.clone(),
     );
-    println!();
-    println!("Polling interval: {} ms\n", interval);
-
-    println!("-------------------------------\n");
 
     tokio::spawn(poll_new_fragment_code::poll_new_fragment_code(
-        file_path, proxy, interval,
+        file_paths, proxy, interval,
     ));
 
-    event_loop
-        .run_app(&mut App::new(default_fragment_code))
-        .unwrap();
+    let mut app = App::new(
+        default_fragment_code,
+        project,
+        export_settings,
+        args.cross_compile,
+        args.check_portability,
+        diagnostics,
+        args.safe.then_some(safety::Safety {
+            loop_limit: args.loop_limit,
+            watchdog_timeout: std::time::Duration::from_millis(args.watchdog),
+        }),
+    )
+    .with_adapter_settings(adapter_settings)
+    .with_clock(clock::Clock::new(
+        args.time,
+        args.speed,
+        args.paused,
+        args.step,
+    ))
+    .with_timeline(timeline::Timeline::new(args.timeline, args.timeline_length))
+    .with_hud(args.hud)
+    .with_version_stats(version_stats::VersionStats::new(args.version_history))
+    .with_frame_pacing(frame_pacing);
+
+    event_loop.run_app(&mut app).unwrap();
+
+    if let Some(e) = app.error() {
+        eprintln!("{}", e);
+        return std::process::ExitCode::FAILURE;
+    }
+    std::process::ExitCode::SUCCESS
 }
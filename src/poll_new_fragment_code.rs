@@ -1,36 +1,48 @@
 use std::time::SystemTime;
 
-// check if the fragment code is updated every x ms, if it is, send a message to the main thread
+// check if any of the files is updated every x ms, if it is, send the code of all of them to the main thread
 pub async fn poll_new_fragment_code(
-    file_path: String,
-    channel: winit::event_loop::EventLoopProxy<(Option<SystemTime>, String)>,
+    file_paths: Vec<String>,
+    channel: winit::event_loop::EventLoopProxy<(Option<SystemTime>, Vec<String>)>,
     interval: u64,
 ) {
-    let mut last_modified;
+    let mut last_modified = Vec::with_capacity(file_paths.len());
 
-    loop {
-        if let Ok(metadata) = std::fs::metadata(&file_path) {
-            if let Ok(modified) = metadata.modified() {
-                last_modified = modified;
-                break;
+    for file_path in file_paths.iter() {
+        loop {
+            if let Ok(metadata) = std::fs::metadata(file_path) {
+                if let Ok(modified) = metadata.modified() {
+                    last_modified.push(modified);
+                    break;
+                }
             }
+            tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await;
         }
-        tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await;
     }
 
-    let code = std::fs::read_to_string(&file_path).unwrap();
-    send_code_update(None, code, &channel);
+    if let Some(code) = read_all(&file_paths) {
+        send_code_update(None, code, &channel);
+    }
 
     loop {
         tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await;
 
-        if let Some(code) = is_code_updated(&file_path, &mut last_modified) {
-            send_code_update(Some(last_modified), code, &channel);
+        let mut updated = None;
+        for (file_path, last_modified) in file_paths.iter().zip(last_modified.iter_mut()) {
+            if is_code_updated(file_path, last_modified) {
+                updated = Some(*last_modified);
+            }
+        }
+
+        if let Some(modified) = updated {
+            if let Some(code) = read_all(&file_paths) {
+                send_code_update(Some(modified), code, &channel);
+            }
         }
     }
 }
 
-fn is_code_updated(path: &str, last_modified: &mut std::time::SystemTime) -> Option<String> {
+fn is_code_updated(path: &str, last_modified: &mut std::time::SystemTime) -> bool {
     let Ok(metadata) = std::fs::metadata(path) else {
         eprintln!("\n\nError when getting metadata: {}", path);
         eprintln!("File does not exist or permission denied.\n");
@@ -38,27 +50,36 @@ fn is_code_updated(path: &str, last_modified: &mut std::time::SystemTime) -> Opt
     };
 
     let Ok(modified) = metadata.modified() else {
-        return None;
+        return false;
     };
 
     if modified == *last_modified {
-        return None;
+        return false;
     }
 
     *last_modified = modified;
 
-    std::fs::read_to_string(path)
-        .map_err(|e| eprintln!("\n\nError when reading file: {}\n\n{}", path, e))
-        .ok()
+    true
+}
+
+fn read_all(paths: &[String]) -> Option<Vec<String>> {
+    paths
+        .iter()
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .map_err(|e| eprintln!("\n\nError when reading file: {}\n\n{}", path, e))
+                .ok()
+        })
+        .collect()
 }
 
 fn send_code_update(
     time: Option<SystemTime>,
-    string: String,
-    channel: &winit::event_loop::EventLoopProxy<(Option<SystemTime>, String)>,
+    code: Vec<String>,
+    channel: &winit::event_loop::EventLoopProxy<(Option<SystemTime>, Vec<String>)>,
 ) {
     channel
-        .send_event((time, string))
+        .send_event((time, code))
         .map_err(|e| eprintln!("\n\nError when sending event: {}\n\n", e))
         .ok();
 }
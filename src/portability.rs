@@ -0,0 +1,225 @@
+// check whether the fragment shader would also work on other platforms,
+// by validating it with their capabilities and limits and running the matching naga backend.
+
+use crate::shader::FRAGMENT_ENTRY_POINT;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    WebGpu,
+    WebGl2,
+    Gles3,
+    Gl,
+    Vulkan,
+    Metal,
+    Dx12,
+}
+
+impl Platform {
+    pub const ALL: [Platform; 7] = [
+        Platform::WebGpu,
+        Platform::WebGl2,
+        Platform::Gles3,
+        Platform::Gl,
+        Platform::Vulkan,
+        Platform::Metal,
+        Platform::Dx12,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Platform::WebGpu => "WebGPU",
+            Platform::WebGl2 => "WebGL2",
+            Platform::Gles3 => "GLES 3.0",
+            Platform::Gl => "OpenGL 3.3",
+            Platform::Vulkan => "Vulkan",
+            Platform::Metal => "Metal",
+            Platform::Dx12 => "DX12",
+        }
+    }
+
+    // webgl2 has neither cube array textures nor sample shading.
+    // wgpu's gl backend has cube array textures, but sample shading only from gles 3.2 and gl 4.0
+    fn capabilities(self) -> naga::valid::Capabilities {
+        match self {
+            Platform::WebGl2 => naga::valid::Capabilities::empty(),
+            Platform::Gles3 | Platform::Gl => naga::valid::Capabilities::CUBE_ARRAY_TEXTURES,
+            Platform::WebGpu | Platform::Vulkan | Platform::Metal | Platform::Dx12 => {
+                naga::valid::Capabilities::default()
+            }
+        }
+    }
+
+    fn limits(self) -> wgpu::Limits {
+        match self {
+            Platform::WebGl2 => wgpu::Limits::downlevel_webgl2_defaults(),
+            Platform::Gles3 | Platform::Gl => wgpu::Limits::downlevel_defaults(),
+            Platform::WebGpu | Platform::Vulkan | Platform::Metal | Platform::Dx12 => {
+                wgpu::Limits::default()
+            }
+        }
+    }
+
+    fn write(self, module: &naga::Module, info: &naga::valid::ModuleInfo) -> Result<(), String> {
+        use naga::back::glsl::Version;
+
+        match self {
+            // the shader is already wgsl, validation is all there is
+            Platform::WebGpu => Ok(()),
+            Platform::WebGl2 => crate::cross::write_glsl(
+                module,
+                info,
+                Version::Embedded {
+                    version: 300,
+                    is_webgl: true,
+                },
+            )
+            .map(|_| ()),
+            Platform::Gles3 => {
+                crate::cross::write_glsl(module, info, Version::new_gles(300)).map(|_| ())
+            }
+            Platform::Gl => {
+                crate::cross::write_glsl(module, info, Version::Desktop(330)).map(|_| ())
+            }
+            Platform::Vulkan => crate::cross::write_spirv(module, info).map(|_| ()),
+            Platform::Metal => crate::cross::write_msl(module, info).map(|_| ()),
+            Platform::Dx12 => crate::cross::write_hlsl(module, info).map(|_| ()),
+        }
+    }
+}
+
+pub fn check(module: &naga::Module, platform: Platform) -> Result<(), String> {
+    let info = crate::shader::validate_with_capabilities(module, platform.capabilities())
+        .map_err(|e| e.to_string())?;
+
+    check_limits(module, &info, &platform.limits())?;
+
+    platform.write(module, &info)
+}
+
+pub fn check_all(module: &naga::Module) -> Vec<(Platform, Result<(), String>)> {
+    Platform::ALL
+        .iter()
+        .map(|&platform| (platform, check(module, platform)))
+        .collect()
+}
+
+pub fn print_report(results: &[(Platform, Result<(), String>)]) {
+    println!("Portability:");
+    for (platform, result) in results {
+        match result {
+            Ok(()) => println!("  {:<11} ok", platform.name()),
+            Err(e) => println!("  {:<11} failed: {}", platform.name(), e),
+        }
+    }
+}
+
+// count the resources the fragment entry point uses, and compare them to the per stage limits
+fn check_limits(
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+    limits: &wgpu::Limits,
+) -> Result<(), String> {
+    let Some(entry_point_index) = module
+        .entry_points
+        .iter()
+        .position(|ep| ep.name == FRAGMENT_ENTRY_POINT)
+    else {
+        return Err(format!("entry point `{}` not found", FRAGMENT_ENTRY_POINT));
+    };
+    let entry_point_info = info.get_entry_point(entry_point_index);
+
+    let mut uniform_buffers = 0;
+    let mut storage_buffers = 0;
+    let mut storage_textures = 0;
+    let mut sampled_textures = 0;
+    let mut samplers = 0;
+
+    for (handle, global) in module.global_variables.iter() {
+        if entry_point_info[handle].is_empty() {
+            continue;
+        }
+        let Some(binding) = global.binding.as_ref() else {
+            continue;
+        };
+
+        if binding.group >= limits.max_bind_groups {
+            return Err(format!(
+                "`{}` uses bind group {}, but only {} bind groups are available",
+                global.name.as_deref().unwrap_or("?"),
+                binding.group,
+                limits.max_bind_groups
+            ));
+        }
+
+        let (inner, count) = match module.types[global.ty].inner {
+            naga::TypeInner::BindingArray { base, size } => (
+                &module.types[base].inner,
+                match size {
+                    naga::ArraySize::Constant(size) => size.get(),
+                    _ => 1,
+                },
+            ),
+            ref inner => (inner, 1),
+        };
+
+        match global.space {
+            naga::AddressSpace::Uniform => {
+                uniform_buffers += count;
+
+                let size = inner.size(module.to_ctx());
+                if size > limits.max_uniform_buffer_binding_size {
+                    return Err(format!(
+                        "uniform `{}` is {} bytes, but the limit is {} bytes",
+                        global.name.as_deref().unwrap_or("?"),
+                        size,
+                        limits.max_uniform_buffer_binding_size
+                    ));
+                }
+            }
+            naga::AddressSpace::Storage { .. } => storage_buffers += count,
+            naga::AddressSpace::Handle => match *inner {
+                naga::TypeInner::Image {
+                    class: naga::ImageClass::Storage { .. },
+                    ..
+                } => storage_textures += count,
+                naga::TypeInner::Image { .. } => sampled_textures += count,
+                naga::TypeInner::Sampler { .. } => samplers += count,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    for (name, used, limit) in [
+        (
+            "uniform buffers",
+            uniform_buffers,
+            limits.max_uniform_buffers_per_shader_stage,
+        ),
+        (
+            "storage buffers",
+            storage_buffers,
+            limits.max_storage_buffers_per_shader_stage,
+        ),
+        (
+            "storage textures",
+            storage_textures,
+            limits.max_storage_textures_per_shader_stage,
+        ),
+        (
+            "sampled textures",
+            sampled_textures,
+            limits.max_sampled_textures_per_shader_stage,
+        ),
+        ("samplers", samplers, limits.max_samplers_per_shader_stage),
+    ] {
+        if used > limit {
+            return Err(format!(
+                "uses {} {} in the fragment stage, but the limit is {}",
+                used, name, limit
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,356 @@
+// a project is a directory with a `live-wgsl.json` manifest and a shader file per pass.
+// passes run in order, every pass but the last renders into a buffer of the window size,
+// which the passes can read through their channels. the last pass renders to the window.
+// a single shader file is a project with one pass and no channels.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::gpu::renderer::{Channel, ChannelSource, PassDescriptor, CHANNEL_COUNT};
+use crate::lint::Lint;
+use crate::shader::{Language, ParsedShader, ShaderError};
+
+pub const MANIFEST_NAME: &str = "live-wgsl.json";
+
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    // glsl code placed before the code of every glsl pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub common: Option<PathBuf>,
+    pub passes: Vec<PassManifest>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PassManifest {
+    pub name: String,
+    pub path: PathBuf,
+    // iChannel0 to iChannel3, `null` for none
+    #[serde(default)]
+    pub channels: Vec<Option<ChannelManifest>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChannelManifest {
+    #[serde(flatten)]
+    pub input: ChannelInput,
+    #[serde(default)]
+    pub filter: Filter,
+    #[serde(default)]
+    pub wrap: Wrap,
+    // only used by images
+    #[serde(default)]
+    pub vflip: bool,
+    #[serde(default)]
+    pub srgb: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ChannelInput {
+    // the output of a pass, by name
+    Buffer { pass: String },
+    // an image file, relative to the project directory
+    Image { path: PathBuf },
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Wrap {
+    #[default]
+    Clamp,
+    Repeat,
+}
+
+pub struct Project {
+    // paths in the manifest are relative to this
+    pub root: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Project {
+    // a directory or a `.json` file is a project, anything else a shader file
+    pub fn is_project_path(path: &Path) -> bool {
+        path.is_dir() || path.extension().is_some_and(|e| e == "json")
+    }
+
+    pub fn single(path: &Path) -> Self {
+        Self {
+            root: PathBuf::new(),
+            manifest: Manifest {
+                name: path.to_string_lossy().into_owned(),
+                common: None,
+                passes: vec![PassManifest {
+                    name: "Image".to_string(),
+                    path: path.to_path_buf(),
+                    channels: Vec::new(),
+                }],
+            },
+        }
+    }
+
+    // the project directory or its manifest
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let manifest_path = if path.is_dir() {
+            path.join(MANIFEST_NAME)
+        } else {
+            path.to_path_buf()
+        };
+
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+        let manifest: Manifest = serde_json::from_str(&manifest)
+            .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+
+        let project = Self {
+            root: manifest_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            manifest,
+        };
+        project.check()?;
+
+        Ok(project)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let passes = &self.manifest.passes;
+        if passes.is_empty() {
+            return Err("the project has no passes".to_string());
+        }
+
+        for file in self.files() {
+            if !file.is_file() {
+                return Err(format!("{}: file not found", file.display()));
+            }
+        }
+
+        for pass in passes {
+            if pass.channels.len() > CHANNEL_COUNT {
+                return Err(format!(
+                    "`{}` has {} channels, at most {} can be used",
+                    pass.name,
+                    pass.channels.len(),
+                    CHANNEL_COUNT
+                ));
+            }
+            for channel in pass.channels.iter().flatten() {
+                if let ChannelInput::Buffer { pass: name } = &channel.input {
+                    match self.pass_index(name) {
+                        Some(index) if index + 1 < passes.len() => {}
+                        Some(_) => {
+                            return Err(format!(
+                                "`{}` reads `{}`, which renders to the window and has no buffer",
+                                pass.name, name
+                            ))
+                        }
+                        None => {
+                            return Err(format!(
+                                "`{}` reads `{}`, which is not a pass",
+                                pass.name, name
+                            ))
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn pass_index(&self, name: &str) -> Option<usize> {
+        self.manifest
+            .passes
+            .iter()
+            .position(|pass| pass.name == name)
+    }
+
+    pub fn is_single(&self) -> bool {
+        self.manifest.passes.len() == 1 && self.manifest.common.is_none()
+    }
+
+    pub fn pass_path(&self, index: usize) -> PathBuf {
+        self.root.join(&self.manifest.passes[index].path)
+    }
+
+    // the pass rendering to the window
+    pub fn image_index(&self) -> usize {
+        self.manifest.passes.len() - 1
+    }
+
+    pub fn image_path(&self) -> PathBuf {
+        self.pass_path(self.image_index())
+    }
+
+    // every file to watch: the passes in order, then the common code
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<_> = (0..self.manifest.passes.len())
+            .map(|index| self.pass_path(index))
+            .collect();
+        files.extend(
+            self.manifest
+                .common
+                .as_ref()
+                .map(|common| self.root.join(common)),
+        );
+        files
+    }
+
+    // the code of the common file, from the sources of `files`
+    fn common_code<'a>(&self, sources: &'a [String]) -> &'a str {
+        match self.manifest.common {
+            Some(_) => &sources[self.manifest.passes.len()],
+            None => "",
+        }
+    }
+
+    pub fn language(&self, index: usize) -> Language {
+        Language::from_path(&self.manifest.passes[index].path)
+    }
+
+    pub fn parse_pass(
+        &self,
+        index: usize,
+        sources: &[String],
+    ) -> Result<ParsedShader, ShaderError> {
+        match self.language(index) {
+            Language::Wgsl => crate::shader::parse(&sources[index], Language::Wgsl),
+            Language::ShadertoyGlsl => crate::shadertoy::import::parse_pass(
+                self.common_code(sources),
+                &sources[index],
+                index == self.image_index(),
+            ),
+        }
+    }
+
+    // the lints of a pass, in the code of its file.
+    // the common code is not linted, its functions are usually used by some passes only.
+    pub fn lint_pass(&self, index: usize, sources: &[String]) -> Vec<Lint> {
+        let Ok(shader) = self.parse_pass(index, sources) else {
+            return Vec::new();
+        };
+        crate::lint::lint(&shader.module, &shader.info)
+            .into_iter()
+            .map(|lint| Lint {
+                span: self.pass_span(index, sources, lint.span),
+                message: lint.message,
+            })
+            .filter(|lint| lint.span.is_defined())
+            .collect()
+    }
+
+    // a span in the module of a pass, in the code of its file.
+    // code the glsl import adds has no span there.
+    pub fn pass_span(&self, index: usize, sources: &[String], span: naga::Span) -> naga::Span {
+        match self.language(index) {
+            Language::Wgsl => span,
+            Language::ShadertoyGlsl => crate::shadertoy::import::pass_span(
+                self.common_code(sources),
+                &sources[index],
+                span,
+            ),
+        }
+    }
+
+    // the source for wgpu of every pass, or the index of the pass failing with its error.
+    // every pass is parsed by naga here rather than by wgpu, which only reports errors as plain text.
+    // with a loop limit, every loop of the passes is capped to it.
+    pub fn shader_sources(
+        &self,
+        sources: &[String],
+        loop_limit: Option<u32>,
+    ) -> Result<Vec<wgpu::ShaderSource<'static>>, (usize, ShaderError)> {
+        (0..self.manifest.passes.len())
+            .map(|index| {
+                let mut module = self
+                    .parse_pass(index, sources)
+                    .map_err(|e| (index, e))?
+                    .module;
+                if let Some(limit) = loop_limit {
+                    crate::safety::limit_loops(&mut module, limit);
+                }
+                Ok(wgpu::ShaderSource::Naga(std::borrow::Cow::Owned(module)))
+            })
+            .collect()
+    }
+
+    // the passes for the renderer, loading the images they read.
+    // images which can not be loaded are reported and left empty.
+    pub fn pass_descriptors<'a>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        sources: Vec<wgpu::ShaderSource<'a>>,
+    ) -> Vec<PassDescriptor<'a>> {
+        sources
+            .into_iter()
+            .zip(self.manifest.passes.iter())
+            .map(|(source, pass)| PassDescriptor {
+                source,
+                channels: std::array::from_fn(|index| {
+                    let channel = pass.channels.get(index)?.as_ref()?;
+                    self.channel(device, queue, &pass.name, index, channel)
+                }),
+            })
+            .collect()
+    }
+
+    fn channel(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pass_name: &str,
+        index: usize,
+        channel: &ChannelManifest,
+    ) -> Option<Channel> {
+        let source = match &channel.input {
+            ChannelInput::Buffer { pass } => ChannelSource::Buffer(self.pass_index(pass)?),
+            ChannelInput::Image { path } => {
+                let path = self.root.join(path);
+                match crate::gpu::texture::load_image(
+                    device,
+                    queue,
+                    &path,
+                    channel.vflip,
+                    channel.srgb,
+                ) {
+                    Ok(view) => ChannelSource::Texture(view),
+                    Err(e) => {
+                        eprintln!(
+                            "\nError when loading iChannel{} of `{}`: {}\n{}",
+                            index,
+                            pass_name,
+                            path.display(),
+                            e
+                        );
+                        return None;
+                    }
+                }
+            }
+        };
+
+        let filter = match channel.filter {
+            Filter::Nearest => wgpu::FilterMode::Nearest,
+            Filter::Linear => wgpu::FilterMode::Linear,
+        };
+        let address_mode = match channel.wrap {
+            Wrap::Clamp => wgpu::AddressMode::ClampToEdge,
+            Wrap::Repeat => wgpu::AddressMode::Repeat,
+        };
+
+        Some(Channel {
+            source,
+            sampler: crate::gpu::texture::create_sampler(device, filter, address_mode),
+        })
+    }
+}
@@ -0,0 +1,146 @@
+// `--safe`: keep an accidental infinite loop from freezing the desktop.
+// every loop of the shader gets a counter which breaks out of it after `loop_limit` iterations,
+// and a watchdog reverts to the last working shader when a frame does not finish in time.
+
+use naga::{
+    BinaryOperator, Block, Expression, Function, Literal, LocalVariable, Scalar, Span, Statement,
+    Type, TypeInner,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Safety {
+    // iterations of a loop each time it is entered
+    pub loop_limit: u32,
+    pub watchdog_timeout: std::time::Duration,
+}
+
+// add an iteration cap to every loop in the module.
+// loops are `for`, `while` and `loop` in wgsl and all loops in glsl, naga has one kind of loop.
+pub fn limit_loops(module: &mut naga::Module, limit: u32) {
+    let u32_type = module.types.insert(
+        Type {
+            name: None,
+            inner: TypeInner::Scalar(Scalar::U32),
+        },
+        Span::UNDEFINED,
+    );
+
+    for (_, function) in module.functions.iter_mut() {
+        limit_loops_in_function(function, u32_type, limit);
+    }
+    for entry_point in module.entry_points.iter_mut() {
+        limit_loops_in_function(&mut entry_point.function, u32_type, limit);
+    }
+}
+
+fn limit_loops_in_function(function: &mut Function, u32_type: naga::Handle<Type>, limit: u32) {
+    let mut body = std::mem::take(&mut function.body);
+    limit_loops_in_block(function, &mut body, u32_type, limit);
+    function.body = body;
+}
+
+fn limit_loops_in_block(
+    function: &mut Function,
+    block: &mut Block,
+    u32_type: naga::Handle<Type>,
+    limit: u32,
+) {
+    let statements = std::mem::take(block);
+    for (mut statement, span) in statements.span_into_iter() {
+        match &mut statement {
+            Statement::Block(inner) => limit_loops_in_block(function, inner, u32_type, limit),
+            Statement::If { accept, reject, .. } => {
+                limit_loops_in_block(function, accept, u32_type, limit);
+                limit_loops_in_block(function, reject, u32_type, limit);
+            }
+            Statement::Switch { cases, .. } => {
+                for case in cases.iter_mut() {
+                    limit_loops_in_block(function, &mut case.body, u32_type, limit);
+                }
+            }
+            Statement::Loop {
+                body, continuing, ..
+            } => {
+                limit_loops_in_block(function, body, u32_type, limit);
+                limit_loops_in_block(function, continuing, u32_type, limit);
+
+                // the counter starts from 0 every time the loop is entered, for nested loops
+                let counter = function.local_variables.append(
+                    LocalVariable {
+                        name: Some("_live_wgsl_loop_counter".to_string()),
+                        ty: u32_type,
+                        init: None,
+                    },
+                    Span::UNDEFINED,
+                );
+                let pointer = function
+                    .expressions
+                    .append(Expression::LocalVariable(counter), Span::UNDEFINED);
+                let zero = literal(function, 0);
+                let one = literal(function, 1);
+                let limit = literal(function, limit);
+                block.push(
+                    Statement::Store {
+                        pointer,
+                        value: zero,
+                    },
+                    Span::UNDEFINED,
+                );
+
+                // if counter >= limit { break; }
+                // counter = counter + 1;
+                let start = function.expressions.len();
+                let count = function
+                    .expressions
+                    .append(Expression::Load { pointer }, Span::UNDEFINED);
+                let is_over = function.expressions.append(
+                    Expression::Binary {
+                        op: BinaryOperator::GreaterEqual,
+                        left: count,
+                        right: limit,
+                    },
+                    Span::UNDEFINED,
+                );
+                let next = function.expressions.append(
+                    Expression::Binary {
+                        op: BinaryOperator::Add,
+                        left: count,
+                        right: one,
+                    },
+                    Span::UNDEFINED,
+                );
+
+                let mut guard = Block::new();
+                guard.push(
+                    Statement::Emit(function.expressions.range_from(start)),
+                    Span::UNDEFINED,
+                );
+                guard.push(
+                    Statement::If {
+                        condition: is_over,
+                        accept: Block::from_vec(vec![Statement::Break]),
+                        reject: Block::new(),
+                    },
+                    Span::UNDEFINED,
+                );
+                guard.push(
+                    Statement::Store {
+                        pointer,
+                        value: next,
+                    },
+                    Span::UNDEFINED,
+                );
+                guard.append(body);
+                *body = guard;
+            }
+            _ => {}
+        }
+        block.push(statement, span);
+    }
+}
+
+fn literal(function: &mut Function, value: u32) -> naga::Handle<Expression> {
+    function
+        .expressions
+        .append(Expression::Literal(Literal::U32(value)), Span::UNDEFINED)
+}
@@ -0,0 +1,121 @@
+// parse and validate fragment shaders with naga, without needing a gpu.
+
+use std::path::Path;
+
+pub const FRAGMENT_ENTRY_POINT: &str = "fs_main";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Wgsl,
+    // see `shadertoy::import`
+    ShadertoyGlsl,
+}
+
+impl Language {
+    // `.glsl` and `.frag` files are shadertoy glsl, anything else is wgsl
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("glsl" | "frag") => Language::ShadertoyGlsl,
+            _ => Language::Wgsl,
+        }
+    }
+}
+
+pub struct ParsedShader {
+    pub module: naga::Module,
+    pub info: naga::valid::ModuleInfo,
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Parse(Box<naga::front::wgsl::ParseError>),
+    GlslParse(Box<naga::front::glsl::ParseErrors>),
+    Validation(Box<naga::WithSpan<naga::valid::ValidationError>>),
+}
+
+impl ShaderError {
+    // codespan style, with colors when stderr is a terminal
+    pub fn emit_to_stderr(&self, source: &str, path: &str) {
+        use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+        use std::io::IsTerminal;
+
+        // naga only writes wgsl errors to stderr in color, even when it is redirected, or to a string
+        let color = std::io::stderr().is_terminal();
+        let color_choice = if color {
+            ColorChoice::Auto
+        } else {
+            ColorChoice::Never
+        };
+
+        match self {
+            ShaderError::Parse(e) if color => e.emit_to_stderr_with_path(source, path),
+            ShaderError::Parse(e) => eprint!("{}", e.emit_to_string_with_path(source, path)),
+            ShaderError::GlslParse(e) => {
+                let writer = StandardStream::stderr(color_choice);
+                e.emit_to_writer_with_path(&mut writer.lock(), source, path);
+            }
+            ShaderError::Validation(e) if color => e.emit_to_stderr_with_path(source, path),
+            ShaderError::Validation(e) => eprint!("{}", e.emit_to_string_with_path(source, path)),
+        }
+    }
+}
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderError::Parse(e) => write!(f, "{}", e),
+            ShaderError::GlslParse(e) => {
+                for error in e.errors.iter() {
+                    writeln!(f, "{}", error.kind)?;
+                }
+                Ok(())
+            }
+            ShaderError::Validation(e) => write!(f, "{}", e.as_inner()),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+// read and parse a shader file for the one-shot commands, printing what went wrong
+pub fn load(path: &str) -> Option<(String, ParsedShader)> {
+    let code = match std::fs::read_to_string(path) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error when reading file: {}\n{}", path, e);
+            return None;
+        }
+    };
+
+    match parse(&code, Language::from_path(Path::new(path))) {
+        Ok(shader) => Some((code, shader)),
+        Err(e) => {
+            e.emit_to_stderr(&code, path);
+            None
+        }
+    }
+}
+
+pub fn parse(code: &str, language: Language) -> Result<ParsedShader, ShaderError> {
+    if language == Language::ShadertoyGlsl {
+        return crate::shadertoy::import::parse(code);
+    }
+
+    let module = naga::front::wgsl::parse_str(code).map_err(|e| ShaderError::Parse(Box::new(e)))?;
+    let info = validate(&module)?;
+
+    Ok(ParsedShader { module, info })
+}
+
+pub fn validate(module: &naga::Module) -> Result<naga::valid::ModuleInfo, ShaderError> {
+    validate_with_capabilities(module, crate::features::capabilities())
+}
+
+pub fn validate_with_capabilities(
+    module: &naga::Module,
+    capabilities: naga::valid::Capabilities,
+) -> Result<naga::valid::ModuleInfo, ShaderError> {
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), capabilities)
+        .validate(module)
+        .map_err(|e| ShaderError::Validation(Box::new(e)))
+}
@@ -0,0 +1,104 @@
+// shadertoy interop.
+// shadertoy shaders get their inputs from fixed uniforms like `iResolution` and `iTime`,
+// where live-wgsl shaders read them from the `Info` uniform.
+
+pub mod export;
+pub mod import;
+pub mod json;
+
+// the members of `Info` (see `ViewportInfo`) by offset, and the shadertoy input each of them maps to
+pub const INFO_MEMBERS: [InfoMember; 6] = [
+    InfoMember::ViewportSize,
+    InfoMember::TimeFromStartUp,
+    InfoMember::TimeFromUpdate,
+    InfoMember::Mouse,
+    InfoMember::Frame,
+    InfoMember::TimeDelta,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InfoMember {
+    ViewportSize,
+    TimeFromStartUp,
+    TimeFromUpdate,
+    Mouse,
+    Frame,
+    TimeDelta,
+}
+
+impl InfoMember {
+    pub fn offset(self) -> u32 {
+        match self {
+            InfoMember::ViewportSize => 0,
+            InfoMember::TimeFromStartUp => 8,
+            InfoMember::TimeFromUpdate => 12,
+            InfoMember::Mouse => 16,
+            InfoMember::Frame => 32,
+            InfoMember::TimeDelta => 36,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            InfoMember::ViewportSize => "viewport_size",
+            InfoMember::TimeFromStartUp => "time_from_start_up",
+            InfoMember::TimeFromUpdate => "time_from_update",
+            InfoMember::Mouse => "mouse",
+            InfoMember::Frame => "frame",
+            InfoMember::TimeDelta => "time_delta",
+        }
+    }
+
+    // shadertoy has no notion of reloading, so both times are `iTime`.
+    // `iMouse` has bottom left origin, and only z tells whether the button is held.
+    pub fn shadertoy_expression(self) -> &'static str {
+        match self {
+            InfoMember::ViewportSize => "iResolution.xy",
+            InfoMember::TimeFromStartUp | InfoMember::TimeFromUpdate => "iTime",
+            InfoMember::Mouse => {
+                "vec4(iMouse.x, iResolution.y - iMouse.y, iMouse.z, sign(iMouse.z) * (iResolution.y - abs(iMouse.w)))"
+            }
+            InfoMember::Frame => "uint(iFrame)",
+            InfoMember::TimeDelta => "iTimeDelta",
+        }
+    }
+
+    pub fn is_type(self, inner: &naga::TypeInner) -> bool {
+        const F32: naga::Scalar = naga::Scalar::F32;
+        match self {
+            InfoMember::ViewportSize => matches!(
+                *inner,
+                naga::TypeInner::Vector {
+                    size: naga::VectorSize::Bi,
+                    scalar: F32,
+                }
+            ),
+            InfoMember::TimeFromStartUp | InfoMember::TimeFromUpdate | InfoMember::TimeDelta => {
+                matches!(*inner, naga::TypeInner::Scalar(F32))
+            }
+            InfoMember::Frame => matches!(*inner, naga::TypeInner::Scalar(naga::Scalar::U32)),
+            InfoMember::Mouse => matches!(
+                *inner,
+                naga::TypeInner::Vector {
+                    size: naga::VectorSize::Quad,
+                    scalar: F32,
+                }
+            ),
+        }
+    }
+
+    // whether the value differs between frames without input
+    pub fn changes_with_time(self) -> bool {
+        match self {
+            InfoMember::ViewportSize | InfoMember::Mouse => false,
+            InfoMember::TimeFromStartUp
+            | InfoMember::TimeFromUpdate
+            | InfoMember::Frame
+            | InfoMember::TimeDelta => true,
+        }
+    }
+
+    pub fn from_offset(offset: u32) -> Option<Self> {
+        INFO_MEMBERS.into_iter().find(|m| m.offset() == offset)
+    }
+}
@@ -0,0 +1,383 @@
+// translate a live-wgsl shader into shadertoy glsl.
+// naga's glsl es backend does the translation, then its output is rewritten:
+// the `Info` uniform becomes `iResolution` / `iTime`, and `main` becomes `mainImage`.
+
+use std::path::Path;
+
+use super::InfoMember;
+use crate::shader::{ParsedShader, FRAGMENT_ENTRY_POINT};
+
+const OUTPUT: &str = "_fs2p_location0";
+
+// names the rewritten code introduces, they must not be used by the shader already
+const RESERVED: [&str; 8] = [
+    "mainImage",
+    "fragColor",
+    "fragCoord",
+    "iResolution",
+    "iTime",
+    "iMouse",
+    "iFrame",
+    "iTimeDelta",
+];
+
+// `live-wgsl export-shadertoy`
+pub fn run(path: &str, output: Option<&str>, print: bool) -> std::process::ExitCode {
+    let Some((code, shader)) = crate::shader::load(path) else {
+        return std::process::ExitCode::FAILURE;
+    };
+
+    // shadertoy glsl is already what shadertoy takes
+    let code = match crate::shader::Language::from_path(Path::new(path)) {
+        crate::shader::Language::ShadertoyGlsl => code,
+        crate::shader::Language::Wgsl => match translate(&shader) {
+            Ok(code) => code,
+            Err(e) => {
+                eprintln!("Error when exporting to shadertoy:\n{}", e);
+                return std::process::ExitCode::FAILURE;
+            }
+        },
+    };
+
+    if print {
+        print!("{}", code);
+        return std::process::ExitCode::SUCCESS;
+    }
+
+    let output = match output {
+        Some(output) => Path::new(output).to_path_buf(),
+        None => Path::new(path).with_extension("shadertoy.glsl"),
+    };
+
+    match std::fs::write(&output, code) {
+        Ok(()) => {
+            println!("Exported: {}", output.display());
+            std::process::ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error when writing file: {}\n{}", output.display(), e);
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+pub fn translate(shader: &ParsedShader) -> Result<String, String> {
+    let module = &shader.module;
+
+    let entry_point = module
+        .entry_points
+        .iter()
+        .find(|ep| ep.name == FRAGMENT_ENTRY_POINT && ep.stage == naga::ShaderStage::Fragment)
+        .ok_or_else(|| format!("fragment entry point `{}` not found", FRAGMENT_ENTRY_POINT))?;
+
+    if let Some(naga::Binding::Location { location, .. }) = entry_point
+        .function
+        .arguments
+        .iter()
+        .find_map(|arg| arg.binding.as_ref().filter(|b| !is_position(b)))
+    {
+        return Err(format!(
+            "fragment input @location({}) has no shadertoy equivalent, only @builtin(position) can be used",
+            location
+        ));
+    }
+
+    let info_members = find_info_uniform(module)?;
+
+    // translate
+    let options = naga::back::glsl::Options {
+        version: naga::back::glsl::Version::Embedded {
+            version: 300,
+            is_webgl: true,
+        },
+        ..Default::default()
+    };
+    let pipeline_options = naga::back::glsl::PipelineOptions {
+        shader_stage: naga::ShaderStage::Fragment,
+        entry_point: FRAGMENT_ENTRY_POINT.to_string(),
+        multiview: None,
+    };
+
+    let mut glsl = String::new();
+    naga::back::glsl::Writer::new(
+        &mut glsl,
+        module,
+        &shader.info,
+        &options,
+        &pipeline_options,
+        naga::proc::BoundsCheckPolicies::default(),
+    )
+    .and_then(|mut writer| writer.write())
+    .map_err(|e| e.to_string())?;
+
+    for name in RESERVED {
+        if find_identifier(&glsl, name) {
+            return Err(format!(
+                "`{}` is used by the shader, but the shadertoy version needs this name",
+                name
+            ));
+        }
+    }
+
+    rewrite(&glsl, info_members)
+}
+
+// what each member of the `Info` uniform maps to, if the shader has one.
+// any other resource can not be expressed in a single shadertoy pass.
+fn find_info_uniform(module: &naga::Module) -> Result<Option<Vec<InfoMember>>, String> {
+    let mut info = None;
+
+    for (_, global) in module.global_variables.iter() {
+        let Some(binding) = global.binding.as_ref() else {
+            continue;
+        };
+        let name = global.name.as_deref().unwrap_or("?");
+
+        if global.space != naga::AddressSpace::Uniform || binding.group != 0 || binding.binding != 0
+        {
+            return Err(format!(
+                "`{}` (@group({}) @binding({})) has no shadertoy equivalent, only the `Info` uniform at @group(0) @binding(0) can be used",
+                name, binding.group, binding.binding
+            ));
+        }
+
+        let naga::TypeInner::Struct { ref members, .. } = module.types[global.ty].inner else {
+            return Err(format!("uniform `{}` must be a struct like `Info`", name));
+        };
+
+        let members = members
+            .iter()
+            .map(|member| {
+                InfoMember::from_offset(member.offset)
+                    .filter(|m| m.is_type(&module.types[member.ty].inner))
+                    .ok_or_else(|| {
+                        format!(
+                            "member `{}` of `{}` has no shadertoy equivalent, `Info` is {}",
+                            member.name.as_deref().unwrap_or("?"),
+                            name,
+                            super::INFO_MEMBERS
+                                .map(|m| format!("`{}`", m.name()))
+                                .join(", ")
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        info = Some(members);
+    }
+
+    Ok(info)
+}
+
+fn is_position(binding: &naga::Binding) -> bool {
+    matches!(
+        *binding,
+        naga::Binding::BuiltIn(naga::BuiltIn::Position { .. })
+    )
+}
+
+fn rewrite(glsl: &str, members: Option<Vec<InfoMember>>) -> Result<String, String> {
+    let mut out = String::new();
+    // the struct type and the instance name of the uniform
+    let mut uniform: Option<(String, String)> = None;
+    // every struct with its member names in the glsl source
+    let mut structs: Vec<(String, Vec<String>)> = Vec::new();
+
+    let mut lines = glsl.lines().peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+
+        // shadertoy adds its own header
+        if trimmed.starts_with("#version") || trimmed.starts_with("precision ") {
+            continue;
+        }
+
+        // `uniform Info_block_0Fragment { Info _group_0_binding_0_fs; };`
+        // other uniforms are rejected by `find_info_uniform` already
+        if members.is_some() && uniform.is_none() && trimmed.starts_with("uniform ") {
+            let mut declaration = trimmed
+                .split(['{', ';'])
+                .nth(1)
+                .unwrap_or_default()
+                .split_whitespace();
+            if let (Some(type_name), Some(instance)) = (declaration.next(), declaration.next()) {
+                uniform = Some((type_name.to_string(), instance.to_string()));
+                continue;
+            }
+        }
+
+        if trimmed.starts_with(&format!("layout(location = 0) out vec4 {};", OUTPUT)) {
+            continue;
+        }
+        if trimmed.starts_with("layout(location") && trimmed.contains(" out ") {
+            return Err("only a single color output at @location(0) can be used".to_string());
+        }
+        if trimmed.starts_with("uniform ") {
+            return Err(format!(
+                "`{}` has no shadertoy equivalent",
+                trimmed.trim_end_matches(';')
+            ));
+        }
+
+        out.push_str(line);
+        out.push('\n');
+
+        // remember member names of structs, to find the ones of the uniform later.
+        // naga writes them one per line, `    vec2 viewport_size;`
+        if let Some(name) = trimmed
+            .strip_prefix("struct ")
+            .and_then(|s| s.strip_suffix(" {"))
+        {
+            let mut member_names = Vec::new();
+            while let Some(&member) = lines.peek() {
+                out.push_str(member);
+                out.push('\n');
+                lines.next();
+                if member.trim() == "};" {
+                    break;
+                }
+                if let Some(member_name) = member
+                    .trim()
+                    .strip_suffix(';')
+                    .and_then(|s| s.split_whitespace().last())
+                {
+                    member_names.push(member_name.to_string());
+                }
+            }
+            structs.push((name.to_string(), member_names));
+        }
+    }
+
+    // uniform member name in glsl -> shadertoy expression
+    let mut member_map = Vec::new();
+    let mut constructor = String::new();
+    if let (Some((type_name, _)), Some(members)) = (uniform.as_ref(), members.as_ref()) {
+        let member_names = structs
+            .iter()
+            .find(|(name, _)| name == type_name)
+            .map(|(_, names)| names.clone())
+            .ok_or_else(|| format!("struct `{}` not found in the translated code", type_name))?;
+
+        for (member_name, member) in member_names.iter().zip(members) {
+            member_map.push((member_name.clone(), member.shadertoy_expression()));
+        }
+        constructor = format!(
+            "{}({})",
+            type_name,
+            members
+                .iter()
+                .map(|m| m.shadertoy_expression())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    let out = rewrite_identifiers(&out, |identifier, rest| {
+        match identifier {
+            OUTPUT => return Ok(Some(("fragColor".to_string(), 0))),
+            // wgsl positions are top left origin, shadertoy's are bottom left
+            "gl_FragCoord" => {
+                return Ok(Some((
+                    "vec4(fragCoord.x, iResolution.y - fragCoord.y, 0.0, 1.0)".to_string(),
+                    0,
+                )))
+            }
+            _ => {}
+        }
+
+        if identifier.starts_with("gl_") {
+            return Err(format!("`{}` has no shadertoy equivalent", identifier));
+        }
+
+        if Some(identifier) == uniform.as_ref().map(|(_, instance)| instance.as_str()) {
+            // `info.member`
+            if let Some(after_dot) = rest.strip_prefix('.') {
+                let member_len = after_dot
+                    .find(|c: char| !is_identifier_char(c))
+                    .unwrap_or(after_dot.len());
+                let member_name = &after_dot[..member_len];
+                if let Some((_, expression)) =
+                    member_map.iter().find(|(name, _)| name == member_name)
+                {
+                    return Ok(Some((expression.to_string(), 1 + member_len)));
+                }
+            }
+            // the whole struct
+            return Ok(Some((constructor.clone(), 0)));
+        }
+
+        Ok(None)
+    })?;
+
+    // naga always writes the entry point as `void main() {`
+    let out = out.replacen(
+        "void main() {",
+        "void mainImage(out vec4 fragColor, in vec2 fragCoord) {",
+        1,
+    );
+
+    // removed declarations leave runs of empty lines behind
+    let mut out = out.trim_start().to_string();
+    while out.contains("\n\n\n") {
+        out = out.replace("\n\n\n", "\n\n");
+    }
+
+    Ok(out)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn find_identifier(code: &str, name: &str) -> bool {
+    let mut found = false;
+    let _ = rewrite_identifiers(code, |identifier, _| {
+        found |= identifier == name;
+        Ok(None)
+    });
+    found
+}
+
+// call `f` with every identifier in `code` and the code following it.
+// `f` returns the replacement and how many characters after the identifier it consumes.
+fn rewrite_identifiers(
+    code: &str,
+    mut f: impl FnMut(&str, &str) -> Result<Option<(String, usize)>, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(code.len());
+    let mut rest = code;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        // skip numbers like `1e5` or `2u`, which look like identifiers
+        let prefix = &rest[..start];
+        let in_number = prefix
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_ascii_digit() || c == '.');
+
+        let len = rest[start..]
+            .find(|c: char| !is_identifier_char(c))
+            .unwrap_or(rest.len() - start);
+        let identifier = &rest[start..start + len];
+        let after = &rest[start + len..];
+
+        out.push_str(prefix);
+        match if in_number {
+            None
+        } else {
+            f(identifier, after)?
+        } {
+            Some((replacement, consumed)) => {
+                out.push_str(&replacement);
+                rest = &after[consumed..];
+            }
+            None => {
+                out.push_str(identifier);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+
+    Ok(out)
+}
@@ -0,0 +1,363 @@
+// run shadertoy style glsl (`mainImage`, `iResolution`, `iTime`, `iMouse`, `iChannel0`...) through naga's glsl frontend.
+// the code is wrapped in a prelude declaring the `Info` uniform with the shadertoy inputs on top of it,
+// and a `main` calling `mainImage`. spans in errors are moved back onto the user's code.
+
+use std::path::Path;
+
+use crate::gpu::renderer::CHANNEL_COUNT;
+use crate::shader::{ParsedShader, ShaderError, FRAGMENT_ENTRY_POINT};
+
+const PRELUDE: &str = r#"#version 450
+
+layout(set = 0, binding = 0) uniform Info {
+    vec2 viewport_size;
+    float time_from_start_up;
+    float time_from_update;
+    vec4 mouse;
+    uint frame;
+    float time_delta;
+};
+
+layout(location = 0) out vec4 _live_wgsl_color;
+
+#define iResolution vec3(viewport_size, 1.0)
+#define iTime time_from_start_up
+#define iTimeDelta time_delta
+#define iFrameRate (1.0 / max(time_delta, 1e-6))
+#define iFrame int(frame)
+"#;
+
+// `Info.mouse` is top left origin
+const MOUSE: &str = r#"
+vec4 _live_wgsl_mouse() {
+    if (all(lessThanEqual(abs(mouse), vec4(0.0)))) {
+        return vec4(0.0);
+    }
+    return vec4(mouse.x, viewport_size.y - mouse.y, mouse.z, sign(mouse.w) * (viewport_size.y - abs(mouse.w)));
+}
+
+#define iMouse _live_wgsl_mouse()
+"#;
+
+const CHANNEL_RESOLUTION: &str = "#define iChannelResolution vec3[4](vec3(textureSize(_live_wgsl_channel0, 0), 1.0), vec3(textureSize(_live_wgsl_channel1, 0), 1.0), vec3(textureSize(_live_wgsl_channel2, 0), 1.0), vec3(textureSize(_live_wgsl_channel3, 0), 1.0))\n";
+
+// the prelude declares the channels and the mouse only where the code uses them,
+// so converted shaders have no bindings they never read.
+// `iChannelResolution` only needs the textures, not their samplers
+fn prelude(common: &str, code: &str) -> String {
+    let uses = |name: &str| common.contains(name) || code.contains(name);
+
+    let mut prelude = PRELUDE.to_string();
+    let all_channels = uses("iChannelResolution");
+    for channel in 0..CHANNEL_COUNT {
+        let sampled = uses(&format!("iChannel{}", channel));
+        if sampled || all_channels {
+            prelude += &format!(
+                "\nlayout(set = 0, binding = {}) uniform texture2D _live_wgsl_channel{};\n",
+                1 + 2 * channel,
+                channel
+            );
+        }
+        if sampled {
+            prelude += &format!(
+                "layout(set = 0, binding = {}) uniform sampler _live_wgsl_channel{channel}_sampler;\n\
+                 #define iChannel{channel} sampler2D(_live_wgsl_channel{channel}, _live_wgsl_channel{channel}_sampler)\n",
+                2 + 2 * channel,
+            );
+        }
+    }
+    if all_channels {
+        prelude += CHANNEL_RESOLUTION;
+    }
+    if uses("iMouse") {
+        prelude += MOUSE;
+    }
+    prelude += "\n";
+    prelude
+}
+
+// the image pass is shown with top left origin, so `fragCoord` is flipped to shadertoy's bottom left.
+// shadertoy ignores alpha there
+const IMAGE_EPILOGUE: &str = r#"
+
+void main() {
+    vec4 color = vec4(0.0);
+    mainImage(color, vec2(gl_FragCoord.x, viewport_size.y - gl_FragCoord.y));
+    _live_wgsl_color = vec4(color.rgb, 1.0);
+}
+"#;
+
+// buffers are only read by passes, not flipping them keeps the texture coordinates `fragCoord / iResolution.xy`
+const BUFFER_EPILOGUE: &str = r#"
+
+void main() {
+    vec4 color = vec4(0.0);
+    mainImage(color, gl_FragCoord.xy);
+    _live_wgsl_color = color;
+}
+"#;
+
+pub fn parse(code: &str) -> Result<ParsedShader, ShaderError> {
+    parse_pass("", code, true)
+}
+
+// a pass of a project, with the project's common code placed before it.
+// `image` is whether the pass renders to the window, rather than a buffer.
+// errors in the common code are reported without a location.
+pub fn parse_pass(common: &str, code: &str, image: bool) -> Result<ParsedShader, ShaderError> {
+    let epilogue = if image {
+        IMAGE_EPILOGUE
+    } else {
+        BUFFER_EPILOGUE
+    };
+    let prelude = prelude(common, code);
+    let source = format!("{}{}\n{}{}", prelude, common, code, epilogue);
+    let offset = prelude.len() + common.len() + 1;
+    let shift = |span: naga::Span| shift_span(span, offset, code.len());
+
+    let mut module = naga::front::glsl::Frontend::default()
+        .parse(
+            &naga::front::glsl::Options::from(naga::ShaderStage::Fragment),
+            &source,
+        )
+        .map_err(|mut e| {
+            for error in e.errors.iter_mut() {
+                error.meta = shift(error.meta);
+            }
+            ShaderError::GlslParse(Box::new(e))
+        })?;
+
+    // the renderer looks for `fs_main`
+    for entry_point in module.entry_points.iter_mut() {
+        entry_point.name = FRAGMENT_ENTRY_POINT.to_string();
+    }
+
+    // name the bindings like wgsl shaders do
+    for (_, global) in module.global_variables.iter_mut() {
+        if global.space == naga::AddressSpace::Uniform {
+            global.name = Some("info".to_string());
+        } else if let Some(name) = global
+            .name
+            .as_deref()
+            .and_then(|name| name.strip_prefix("_live_wgsl_channel"))
+        {
+            global.name = Some(format!("channel{}", name));
+        }
+    }
+
+    let info = crate::shader::validate(&module).map_err(|e| match e {
+        ShaderError::Validation(e) => {
+            let spans: Vec<_> = e.spans().cloned().collect();
+            let mut shifted = naga::WithSpan::new(e.into_inner());
+            for (span, label) in spans {
+                shifted = shifted.with_span(shift(span), label);
+            }
+            ShaderError::Validation(Box::new(shifted))
+        }
+        e => e,
+    })?;
+
+    Ok(ParsedShader { module, info })
+}
+
+// a span of the module `parse_pass` made, in `code`
+pub fn pass_span(common: &str, code: &str, span: naga::Span) -> naga::Span {
+    shift_span(
+        span,
+        prelude(common, code).len() + common.len() + 1,
+        code.len(),
+    )
+}
+
+// spans outside of the user's code can not be shown in it, they are dropped
+fn shift_span(span: naga::Span, offset: usize, code_len: usize) -> naga::Span {
+    match span.to_range() {
+        Some(range) if range.start >= offset && range.end <= offset + code_len => {
+            naga::Span::new((range.start - offset) as u32, (range.end - offset) as u32)
+        }
+        _ => naga::Span::default(),
+    }
+}
+
+pub fn to_wgsl(shader: &ParsedShader) -> Result<String, String> {
+    let mut module = shader.module.clone();
+    rename_prelude(&mut module);
+    name_expressions(&mut module, &shader.info);
+
+    let mut code = String::new();
+    naga::back::wgsl::Writer::new(&mut code, naga::back::wgsl::WriterFlags::empty())
+        .write(&module, &shader.info)
+        .map_err(|e| e.to_string())?;
+
+    Ok(code)
+}
+
+// what the prelude and the glsl frontend declare, named like a wgsl shader would
+fn rename_prelude(module: &mut naga::Module) {
+    let rename = |name: &mut Option<String>, from: &str, to: &str| {
+        if name.as_deref() == Some(from) {
+            *name = Some(to.to_string());
+        }
+    };
+
+    for (_, global) in module.global_variables.iter_mut() {
+        rename(&mut global.name, "_live_wgsl_color", "frag_color");
+        rename(&mut global.name, "gl_FragCoord", "frag_coord");
+    }
+    for (_, function) in module.functions.iter_mut() {
+        rename(&mut function.name, "_live_wgsl_mouse", "shadertoy_mouse");
+    }
+
+    let mut outputs = Vec::new();
+    for entry_point in module.entry_points.iter_mut() {
+        for argument in entry_point.function.arguments.iter_mut() {
+            rename(&mut argument.name, "gl_FragCoord", "position");
+        }
+        outputs.extend(entry_point.function.result.as_ref().map(|result| result.ty));
+    }
+    for ty in outputs {
+        let mut output = module.types[ty].clone();
+        if let naga::TypeInner::Struct {
+            ref mut members, ..
+        } = output.inner
+        {
+            for member in members.iter_mut() {
+                rename(&mut member.name, "_live_wgsl_color", "color");
+            }
+            module.types.replace(ty, output);
+        }
+    }
+}
+
+// the wgsl writer stores expressions used more than once in `let _eN`,
+// they are named after what they load instead
+fn name_expressions(module: &mut naga::Module, info: &naga::valid::ModuleInfo) {
+    let mut names = Vec::new();
+    for (handle, function) in module.functions.iter() {
+        names.push(expression_names(module, function, &info[handle]));
+    }
+    for (function, names) in module.functions.iter_mut().map(|(_, f)| f).zip(names) {
+        function.named_expressions.extend(names);
+    }
+
+    let mut names = Vec::new();
+    for (index, entry_point) in module.entry_points.iter().enumerate() {
+        names.push(expression_names(
+            module,
+            &entry_point.function,
+            info.get_entry_point(index),
+        ));
+    }
+    for (entry_point, names) in module.entry_points.iter_mut().zip(names) {
+        entry_point.function.named_expressions.extend(names);
+    }
+}
+
+fn expression_names(
+    module: &naga::Module,
+    function: &naga::Function,
+    info: &naga::valid::FunctionInfo,
+) -> Vec<(naga::Handle<naga::Expression>, String)> {
+    use naga::Expression;
+
+    function
+        .expressions
+        .iter()
+        .filter(|&(handle, expression)| {
+            // as in the writer
+            let image = matches!(
+                expression,
+                Expression::ImageLoad { .. }
+                    | Expression::ImageQuery { .. }
+                    | Expression::ImageSample { .. }
+            );
+            !function.named_expressions.contains_key(&handle)
+                && (image || expression.bake_ref_count() <= info[handle].ref_count)
+        })
+        .map(|(handle, _)| (handle, expression_name(module, function, info, handle)))
+        .collect()
+}
+
+fn expression_name(
+    module: &naga::Module,
+    function: &naga::Function,
+    info: &naga::valid::FunctionInfo,
+    handle: naga::Handle<naga::Expression>,
+) -> String {
+    use naga::Expression;
+
+    let name = |handle| expression_name(module, function, info, handle);
+    match function.expressions[handle] {
+        Expression::GlobalVariable(global) => module.global_variables[global].name.clone(),
+        Expression::LocalVariable(local) => function.local_variables[local].name.clone(),
+        Expression::FunctionArgument(index) => function.arguments[index as usize].name.clone(),
+        Expression::AccessIndex { base, index } => {
+            let ty = match *info[base].ty.inner_with(&module.types) {
+                naga::TypeInner::Pointer { base, .. } => &module.types[base].inner,
+                ref ty => ty,
+            };
+            match *ty {
+                naga::TypeInner::Struct { ref members, .. } => members[index as usize].name.clone(),
+                _ => Some(name(base)),
+            }
+        }
+        Expression::Load { pointer: base }
+        | Expression::Access { base, .. }
+        | Expression::Swizzle { vector: base, .. }
+        | Expression::As { expr: base, .. } => Some(name(base)),
+        Expression::ImageSample { .. } | Expression::ImageLoad { .. } => Some("texel".to_string()),
+        Expression::ImageQuery { .. } => Some("size".to_string()),
+        _ => None,
+    }
+    .unwrap_or_else(|| "value".to_string())
+}
+
+// `live-wgsl convert`
+pub fn run(path: &str, output: Option<&str>) -> std::process::ExitCode {
+    let Some((_, shader)) = crate::shader::load(path) else {
+        return std::process::ExitCode::FAILURE;
+    };
+
+    let code = match to_wgsl(&shader) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error when converting to wgsl:\n{}", e);
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+
+    let output = match output {
+        Some(output) => Path::new(output).to_path_buf(),
+        None => Path::new(path).with_extension("wgsl"),
+    };
+
+    match std::fs::write(&output, code) {
+        Ok(()) => {
+            println!("Converted: {}", output.display());
+            std::process::ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error when writing file: {}\n{}", output.display(), e);
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shader::Language;
+
+    // `live-wgsl convert` then `live-wgsl check`
+    #[test]
+    fn converted_default_shader_checks_without_warnings() {
+        let shader = super::parse(include_str!("../fragment_default.glsl")).unwrap();
+        let code = super::to_wgsl(&shader).unwrap();
+
+        let converted = crate::shader::parse(&code, Language::Wgsl).unwrap();
+        let errors = crate::check::interface_errors(&converted.module);
+        let lints = crate::lint::lint(&converted.module, &converted.info);
+        let messages: Vec<_> = errors.iter().chain(&lints).map(|l| &l.message).collect();
+        assert!(messages.is_empty(), "{:?}\n{}", messages, code);
+        assert!(!code.contains("_live_wgsl"), "{}", code);
+    }
+}
@@ -0,0 +1,344 @@
+// turn a shadertoy json export into a project.
+// the json is what the shadertoy api returns (`{"Shader": {...}}`), or a shader or a list of shaders saved from the site.
+// every pass becomes a glsl file, and buffer and texture inputs become channels in the manifest.
+// nothing is downloaded, textures are looked up next to the json.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::gpu::renderer::CHANNEL_COUNT;
+use crate::project::{
+    ChannelInput, ChannelManifest, Filter, Manifest, PassManifest, Wrap, MANIFEST_NAME,
+};
+
+// `live-wgsl import --no-run`
+pub fn run(path: &str, output: Option<&str>) -> std::process::ExitCode {
+    match import(path, output) {
+        Some(_) => std::process::ExitCode::SUCCESS,
+        None => std::process::ExitCode::FAILURE,
+    }
+}
+
+// write the project and return its directory
+pub fn import(path: &str, output: Option<&str>) -> Option<PathBuf> {
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Error when reading file: {}\n{}", path, e);
+            return None;
+        }
+    };
+
+    let project_dir = match output {
+        Some(output) => PathBuf::from(output),
+        None => Path::new(path).with_extension(""),
+    };
+    let json_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+
+    match write_project(&json, json_dir, &project_dir) {
+        Ok(()) => {
+            println!("Imported: {}", project_dir.display());
+            Some(project_dir)
+        }
+        Err(e) => {
+            eprintln!("Error when importing: {}\n{}", path, e);
+            None
+        }
+    }
+}
+
+fn write_project(json: &str, json_dir: &Path, project_dir: &Path) -> Result<(), String> {
+    let json: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let shader = find_shader(&json).ok_or("no shader with render passes found")?;
+
+    let mut image = None;
+    let mut buffers = Vec::new();
+    let mut common = None;
+    for pass in shader["renderpass"].as_array().into_iter().flatten() {
+        match pass["type"].as_str().unwrap_or_default() {
+            "image" => image = Some(pass),
+            "buffer" => buffers.push(pass),
+            "common" => common = Some(pass),
+            kind => eprintln!(
+                "`{}` is a {} pass, which is not supported",
+                pass_name(pass),
+                kind
+            ),
+        }
+    }
+    let image = image.ok_or("the shader has no image pass")?;
+
+    // shadertoy runs the buffers in order of their names, then the image
+    buffers.sort_by_key(|pass| pass_name(pass));
+    let passes: Vec<_> = buffers.into_iter().chain(std::iter::once(image)).collect();
+
+    // passes are found by name and each has its own file, even when names are empty or the same
+    let mut names = Vec::new();
+    for pass in &passes {
+        let name = unique(&names, pass_name(pass), |name, n| format!("{} {}", name, n));
+        names.push(name);
+    }
+    let mut file_names = Vec::new();
+    for name in common.map(pass_name).iter().chain(&names) {
+        let file_name = unique(&file_names, file_stem(name), |stem, n| {
+            format!("{}_{}", stem, n)
+        });
+        file_names.push(file_name);
+    }
+    let mut paths = file_names
+        .iter()
+        .map(|file_name| PathBuf::from(format!("{}.glsl", file_name)));
+    let common_path = common.and(paths.next());
+
+    // buffer inputs refer to the outputs of passes by id
+    let outputs: Vec<(String, String)> = passes
+        .iter()
+        .zip(&names)
+        .flat_map(|(pass, name)| {
+            pass["outputs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|output| Some((id(&output["id"])?, name.clone())))
+        })
+        .collect();
+    // the image renders to the window, it has no buffer to read
+    let buffer_names = &names[..names.len() - 1];
+
+    // every channel is resolved before anything is written
+    let mut textures = Vec::new();
+    let mut manifest_passes = Vec::new();
+    for ((pass, name), path) in passes.iter().zip(&names).zip(paths) {
+        let mut channels: Vec<Option<ChannelManifest>> = (0..CHANNEL_COUNT).map(|_| None).collect();
+        for input in pass["inputs"].as_array().into_iter().flatten() {
+            let Some(index) = input["channel"]
+                .as_u64()
+                .map(|index| index as usize)
+                .filter(|index| *index < CHANNEL_COUNT)
+            else {
+                continue;
+            };
+            channels[index] = match channel(input, &outputs, buffer_names, json_dir, project_dir) {
+                Ok(Some((channel, texture))) => {
+                    textures.extend(texture);
+                    Some(channel)
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    eprintln!("iChannel{} of `{}` is left empty: {}", index, name, e);
+                    None
+                }
+            };
+        }
+
+        manifest_passes.push(PassManifest {
+            name: name.clone(),
+            path,
+            channels,
+        });
+    }
+
+    if project_dir.join(MANIFEST_NAME).exists() {
+        return Err(format!(
+            "{} already exists, choose another directory with `-o`",
+            project_dir.join(MANIFEST_NAME).display()
+        ));
+    }
+    std::fs::create_dir_all(project_dir)
+        .map_err(|e| format!("{}: {}", project_dir.display(), e))?;
+
+    let write = |path: &Path, code: &Value| {
+        let path = project_dir.join(path);
+        std::fs::write(&path, code.as_str().unwrap_or_default())
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    };
+
+    if let (Some(common), Some(path)) = (common, &common_path) {
+        write(path, &common["code"])?;
+    }
+    for (pass, manifest_pass) in passes.iter().zip(&manifest_passes) {
+        write(&manifest_pass.path, &pass["code"])?;
+    }
+    for (found, path) in textures {
+        let destination = project_dir.join(&path);
+        std::fs::create_dir_all(project_dir.join("media"))
+            .and_then(|_| std::fs::copy(&found, &destination))
+            .map_err(|e| format!("{}: {}", destination.display(), e))?;
+    }
+
+    let manifest = Manifest {
+        name: shader["info"]["name"]
+            .as_str()
+            .unwrap_or("shadertoy")
+            .to_string(),
+        common: common_path,
+        passes: manifest_passes,
+    };
+    let manifest_path = project_dir.join(MANIFEST_NAME);
+    std::fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| format!("{}: {}", manifest_path.display(), e))
+}
+
+// `name`, or the first of `with_number(name, 2)`, `with_number(name, 3)`... not in `taken`
+fn unique(taken: &[String], name: String, with_number: impl Fn(&str, usize) -> String) -> String {
+    let mut unique = name.clone();
+    let mut number = 2;
+    while taken.contains(&unique) {
+        unique = with_number(&name, number);
+        number += 1;
+    }
+    unique
+}
+
+fn find_shader(json: &Value) -> Option<&Value> {
+    match json {
+        Value::Array(shaders) => {
+            if shaders.len() > 1 {
+                eprintln!(
+                    "{} shaders in the file, only the first one is imported",
+                    shaders.len()
+                );
+            }
+            shaders.first().and_then(find_shader)
+        }
+        Value::Object(object) if object.contains_key("renderpass") => Some(json),
+        Value::Object(object) => object.get("Shader").and_then(find_shader),
+        _ => None,
+    }
+}
+
+// a texture found next to the json, and its path in the project
+type TextureCopy = (PathBuf, PathBuf);
+
+// the channel, and the texture file to copy into the project for it
+fn channel(
+    input: &Value,
+    outputs: &[(String, String)],
+    buffer_names: &[String],
+    json_dir: &Path,
+    project_dir: &Path,
+) -> Result<Option<(ChannelManifest, Option<TextureCopy>)>, String> {
+    // older exports use `ctype` and `src`
+    let kind = input
+        .get("type")
+        .or_else(|| input.get("ctype"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let src = input
+        .get("filepath")
+        .or_else(|| input.get("src"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    let mut texture = None;
+    let channel_input = match kind {
+        "buffer" => {
+            let pass = id(&input["id"])
+                .and_then(|id| outputs.iter().find(|(output, _)| *output == id))
+                .map(|(_, pass)| pass.clone())
+                .or_else(|| previz_buffer(src))
+                .ok_or_else(|| format!("no pass writes the buffer {}", input["id"]))?;
+            if !buffer_names.contains(&pass) {
+                return Err(format!("`{}` is not an imported buffer pass", pass));
+            }
+            ChannelInput::Buffer { pass }
+        }
+        "texture" => {
+            let name = Path::new(src)
+                .file_name()
+                .ok_or_else(|| format!("texture without file: {}", input))?;
+            let path = Path::new("media").join(name);
+
+            texture = [
+                json_dir.join(src.trim_start_matches('/')),
+                json_dir.join(name),
+            ]
+            .into_iter()
+            .find(|candidate| candidate.is_file())
+            .map(|found| (found, path.clone()));
+            if texture.is_none() {
+                eprintln!(
+                    "{} is not found next to the json, put it at {}",
+                    src,
+                    project_dir.join(&path).display()
+                );
+            }
+
+            ChannelInput::Image { path }
+        }
+        "" => return Ok(None),
+        kind => return Err(format!("{} inputs are not supported", kind)),
+    };
+
+    let sampler = &input["sampler"];
+    let channel = ChannelManifest {
+        filter: match sampler["filter"].as_str() {
+            Some("nearest") => Filter::Nearest,
+            // mipmaps are not generated, `mipmap` is linear without them
+            _ => Filter::Linear,
+        },
+        wrap: match sampler["wrap"].as_str() {
+            Some("repeat") => Wrap::Repeat,
+            _ => Wrap::Clamp,
+        },
+        // shadertoy flips textures unless told not to, buffers are never flipped
+        vflip: matches!(channel_input, ChannelInput::Image { .. })
+            && sampler.get("vflip").is_none_or(is_true),
+        srgb: sampler.get("srgb").is_some_and(is_true),
+        input: channel_input,
+    };
+    Ok(Some((channel, texture)))
+}
+
+// `Buffer A`, `Image` and `Common` become `buffer_a`, `image` and `common`, without the extension
+fn file_stem(pass_name: &str) -> String {
+    pass_name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// passes without a name are named after their type
+fn pass_name(pass: &Value) -> String {
+    match pass["name"].as_str().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => match pass["type"].as_str().unwrap_or_default() {
+            "image" => "Image".to_string(),
+            "common" => "Common".to_string(),
+            _ => "Buffer".to_string(),
+        },
+    }
+}
+
+// ids are numbers in older exports and strings in newer ones
+fn id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn is_true(value: &Value) -> bool {
+    match value {
+        Value::Bool(value) => *value,
+        Value::String(value) => value == "true",
+        _ => false,
+    }
+}
+
+// older exports refer to buffers by their preview image, `/media/previz/buffer00.png` is buffer a
+fn previz_buffer(src: &str) -> Option<String> {
+    let index = src
+        .strip_prefix("/media/previz/buffer")?
+        .strip_suffix(".png")?
+        .parse::<u8>()
+        .ok()
+        .filter(|index| *index < 4)?;
+    Some(format!("Buffer {}", (b'A' + index) as char))
+}
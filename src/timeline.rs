@@ -0,0 +1,124 @@
+// the loop region and bookmarks of the clock, and the bar showing them at the bottom of the window.
+// they belong to the session rather than to the code, so they are kept across reloads.
+// while a loop region is set, the time from start up is kept in it, wrapping around at its ends.
+
+use crate::clock::Clock;
+use crate::gpu::timeline_bar::{self, BarState, MAX_BOOKMARKS};
+
+// a bookmark this close to the time is removed instead of adding another one, in seconds
+const BOOKMARK_TOLERANCE: f64 = 0.05;
+
+pub struct Timeline {
+    pub visible: bool,
+    // seconds the bar spans at least, it grows by this to keep the time on it
+    pub length: f64,
+    // the latest time reached, the bar does not shrink back while scrubbing
+    end: f64,
+    pub loop_in: Option<f64>,
+    pub loop_out: Option<f64>,
+    // sorted
+    pub bookmarks: Vec<f64>,
+    // while the left button is held after pressing it on the bar
+    pub scrubbing: bool,
+}
+
+impl Timeline {
+    pub fn new(visible: bool, length: f64) -> Self {
+        Self {
+            visible,
+            length: length.max(1.0),
+            end: 0.0,
+            loop_in: None,
+            loop_out: None,
+            bookmarks: Vec::new(),
+            scrubbing: false,
+        }
+    }
+
+    // seconds from the left to the right end of the bar
+    pub fn duration(&self) -> f64 {
+        let end = self
+            .bookmarks
+            .iter()
+            .copied()
+            .chain(self.loop_out)
+            .fold(self.end, f64::max);
+        self.length * (end / self.length).ceil().max(1.0)
+    }
+
+    // none while the ends are not both set or out of order
+    pub fn loop_region(&self) -> Option<(f64, f64)> {
+        match (self.loop_in, self.loop_out) {
+            (Some(start), Some(end)) if start < end => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    // call after the clock moved
+    pub fn update(&mut self, clock: &mut Clock) {
+        if let Some((start, end)) = self.loop_region() {
+            let time = clock.from_start_up;
+            if time < start || time >= end {
+                clock.jump_to(start + (time - start).rem_euclid(end - start));
+            }
+        }
+        self.end = self.end.max(clock.from_start_up);
+    }
+
+    // add a bookmark at the time, or remove the one there
+    pub fn toggle_bookmark(&mut self, time: f64) {
+        if let Some(index) = self
+            .bookmarks
+            .iter()
+            .position(|bookmark| (bookmark - time).abs() < BOOKMARK_TOLERANCE)
+        {
+            self.bookmarks.remove(index);
+        } else if self.bookmarks.len() < MAX_BOOKMARKS {
+            self.bookmarks.push(time);
+            self.bookmarks.sort_by(f64::total_cmp);
+        } else {
+            println!("\nThere can be at most {} bookmarks", MAX_BOOKMARKS);
+        }
+    }
+
+    pub fn previous_bookmark(&self, time: f64) -> Option<f64> {
+        self.bookmarks
+            .iter()
+            .rev()
+            .copied()
+            .find(|bookmark| *bookmark < time - BOOKMARK_TOLERANCE)
+    }
+
+    pub fn next_bookmark(&self, time: f64) -> Option<f64> {
+        self.bookmarks
+            .iter()
+            .copied()
+            .find(|bookmark| *bookmark > time + BOOKMARK_TOLERANCE)
+    }
+
+    // whether a position in the window, in pixels, is on the bar
+    pub fn contains(&self, position: [f32; 2], window_size: [f32; 2]) -> bool {
+        self.visible && position[1] >= window_size[1] - timeline_bar::height(window_size)
+    }
+
+    // the time under a horizontal position in the window
+    pub fn time_at(&self, x: f32, window_width: f32) -> f64 {
+        let fraction = (x / window_width.max(1.0)).clamp(0.0, 1.0) as f64;
+        fraction * self.duration()
+    }
+
+    pub fn bar_state(&self, time: f64) -> BarState<'_> {
+        BarState {
+            duration: self.duration(),
+            time,
+            loop_region: self.loop_region(),
+            bookmarks: &self.bookmarks,
+        }
+    }
+
+    // for the status line
+    pub fn status(&self) -> Option<String> {
+        let (start, end) = self.loop_region()?;
+        Some(format!("Loop: {:.2}-{:.2}s", start, end))
+    }
+}
@@ -0,0 +1,145 @@
+// gpu time of each version of the code which compiled, to see whether an edit made it faster.
+// once a version rendered enough frames, its median is compared with the one of the version before.
+// the times are of whatever the versions rendered, so a comparison is fair while the clocks are paused
+// or the times compared are alike.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// frames measured before a version is compared
+const COMPARED_SAMPLES: usize = 120;
+// the latest frames of a version its statistics are of
+const ROLLING_SAMPLES: usize = 600;
+
+struct Version {
+    number: usize,
+    // when the code was updated
+    time: Option<String>,
+    // milliseconds
+    gpu_times: VecDeque<f32>,
+    compared: bool,
+}
+
+impl Version {
+    fn median(&self) -> Option<f32> {
+        self.percentile(50.0)
+    }
+
+    // nearest rank
+    fn percentile(&self, percent: f32) -> Option<f32> {
+        if self.gpu_times.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f32> = self.gpu_times.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let rank = (percent / 100.0 * sorted.len() as f32).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+}
+
+pub struct VersionStats {
+    // oldest first, the current one last
+    versions: VecDeque<Version>,
+    history: usize,
+    next_number: usize,
+}
+
+impl VersionStats {
+    // keeping the statistics of the last `history` versions
+    pub fn new(history: usize) -> Self {
+        Self {
+            versions: VecDeque::new(),
+            history: history.max(2),
+            next_number: 1,
+        }
+    }
+
+    // call when new code compiled
+    pub fn begin_version(&mut self, time: Option<&str>) {
+        if self.versions.len() == self.history {
+            self.versions.pop_front();
+        }
+        self.versions.push_back(Version {
+            number: self.next_number,
+            time: time.map(str::to_string),
+            gpu_times: VecDeque::new(),
+            compared: false,
+        });
+        self.next_number += 1;
+    }
+
+    // call once per frame. the comparison with the version before, once the current one has enough frames.
+    pub fn record(&mut self, gpu_time: Option<Duration>) -> Option<String> {
+        let current = self.versions.back_mut()?;
+        if current.gpu_times.len() == ROLLING_SAMPLES {
+            current.gpu_times.pop_front();
+        }
+        current
+            .gpu_times
+            .extend(gpu_time.map(|time| time.as_secs_f32() * 1000.0));
+
+        if current.compared || current.gpu_times.len() < COMPARED_SAMPLES {
+            return None;
+        }
+        current.compared = true;
+        let current = self.versions.back()?;
+        let median = current.median()?;
+        let mut report = format!(
+            "Version {}: median GPU time {:.3} ms, p95 {:.3} ms",
+            current.number,
+            median,
+            current.percentile(95.0)?
+        );
+        if let Some(change) = self.change() {
+            report.push_str(&format!(
+                ", {:+.0}% median GPU time vs version {}",
+                change,
+                current.number - 1
+            ));
+        }
+        Some(report)
+    }
+
+    // percent the median of the current version differs from the one of the version before
+    pub fn change(&self) -> Option<f32> {
+        let mut versions = self.versions.iter().rev();
+        let current = versions.next()?;
+        let previous = versions.next()?;
+        if !current.compared {
+            return None;
+        }
+        let (current, previous) = (current.median()?, previous.median()?);
+        (previous > 0.0).then(|| (current / previous - 1.0) * 100.0)
+    }
+
+    // the medians of the last versions, oldest first
+    pub fn history(&self) -> String {
+        let mut text = String::from("GPU time of the last versions:\n");
+        let mut previous_median = None;
+        for version in &self.versions {
+            let median = version.median();
+            text.push_str(&format!(
+                "  Version {:>3} {:>10}",
+                version.number,
+                version.time.as_deref().unwrap_or("start")
+            ));
+            match median {
+                Some(median) => text.push_str(&format!(
+                    "  median {:>8.3} ms  p95 {:>8.3} ms  ({} frames)",
+                    median,
+                    version.percentile(95.0).unwrap_or(median),
+                    version.gpu_times.len()
+                )),
+                None => text.push_str("  not measured"),
+            }
+            if let (Some(median), Some(previous)) = (median, previous_median) {
+                if previous > 0.0 {
+                    text.push_str(&format!("  {:+.0}%", (median / previous - 1.0) * 100.0));
+                }
+            }
+            text.push('\n');
+            previous_median = median;
+        }
+        text
+    }
+}
@@ -1,46 +1,208 @@
 use chrono::Timelike;
-use std::{sync::Arc, time::SystemTime};
+use std::{
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{ElementState, MouseButton, WindowEvent},
     event_loop::ActiveEventLoop,
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
     window::{Window, WindowId},
 };
 
-use crate::gpu::{GpuState, Renderer};
+use crate::{
+    clock::Clock,
+    complexity::{self, Complexity},
+    diagnostics::Diagnostics,
+    export::ExportSettings,
+    frame_pacing::{self, FramePacing},
+    frame_stats::FrameStats,
+    gpu::{
+        adapter::AdapterSettings, gpu_timer::GpuTimer, hud::Hud, overlay::Overlay,
+        renderer::ViewportInfo, timeline_bar::TimelineBar, watchdog::Watchdog, GpuState, Renderer,
+    },
+    project::Project,
+    safety::Safety,
+    timeline::Timeline,
+    version_stats::VersionStats,
+};
 
 pub struct App<'a> {
     window: Option<Arc<Window>>,
     state: Option<GpuState<'a>>,
+    adapter_settings: AdapterSettings,
     default_fragment_code: &'a str,
     renderer: Option<Renderer>,
+    // errors of the current code on top of the output
+    overlay: Option<Overlay>,
+    // performance numbers, shown with `H`
+    hud: Option<Hud>,
+    hud_visible: bool,
+    // when the numbers of the hud were last written
+    hud_updated: Option<std::time::Instant>,
+    frame_stats: FrameStats,
+    // timestamp queries, none where the adapter has none
+    gpu_timer: Option<GpuTimer>,
+    // milliseconds of each pass, the latest read back
+    pass_times: Vec<f64>,
+    // gpu time of each version of the code
+    version_stats: VersionStats,
+    frame_pacing: FramePacing,
+    // whether the frames of the current code change without input
+    animated: bool,
     // viewport size
     viewport_size: [f32; 2],
     // time
-    time_from_start_up: std::time::Instant,
-    time_from_update: std::time::Instant,
+    clock: Clock,
+    // a time typed with the digit keys, jumped to with enter
+    typed_time: Option<String>,
+    timeline: Timeline,
+    timeline_bar: Option<TimelineBar>,
+    // frames since the last reload
+    frame: u32,
+    time_delta: f32,
     // update time
     updated_time: Option<String>,
+    // mouse
+    cursor_position: [f32; 2],
+    mouse_pressed: bool,
+    mouse: [f32; 4],
+    modifiers: ModifiersState,
+    // shader files
+    project: Project,
+    // export
+    export_settings: ExportSettings,
+    // naga reports
+    cross_compile: bool,
+    check_portability: bool,
+    working_sources: Option<Vec<String>>,
+    // of each pass of the working sources, to show how it changes
+    complexity: Vec<Option<Complexity>>,
+    // to compile again on a new device
+    latest_sources: Option<Vec<String>>,
+    // the working sources before `working_sources`, to go back to when those hang the gpu
+    previous_working_sources: Option<Vec<String>>,
+    // `--safe`
+    safety: Option<Safety>,
+    watchdog: Option<Watchdog>,
+    // frames rendered since the latest code compiled, a device lost in the first ones is put on it
+    frames_since_reload: u32,
+    // terminal output of compiles
+    diagnostics: Diagnostics,
+    // why the event loop exited early
+    error: Option<String>,
 }
 
+// the surface has up to 2 frames in flight, so the first frame of new code is done on the gpu by the third
+const RELOAD_FRAMES: u32 = 3;
+
 impl<'a> App<'a> {
-    pub fn new(default_fragment_code: &'a str) -> Self {
+    pub fn new(
+        default_fragment_code: &'a str,
+        project: Project,
+        export_settings: ExportSettings,
+        cross_compile: bool,
+        check_portability: bool,
+        diagnostics: Diagnostics,
+        safety: Option<Safety>,
+    ) -> Self {
         Self {
             window: None,
             state: None,
+            adapter_settings: AdapterSettings::default(),
             default_fragment_code,
             renderer: None,
+            overlay: None,
+            hud: None,
+            hud_visible: false,
+            hud_updated: None,
+            frame_stats: FrameStats::default(),
+            gpu_timer: None,
+            pass_times: Vec::new(),
+            version_stats: VersionStats::new(10),
+            frame_pacing: FramePacing::new(frame_pacing::PresentMode::Fifo, None, 0.0, false),
+            animated: true,
             viewport_size: [0.0, 0.0],
-            time_from_start_up: std::time::Instant::now(),
-            time_from_update: std::time::Instant::now(),
+            clock: Clock::new(0.0, 1.0, false, 1.0 / 60.0),
+            typed_time: None,
+            timeline: Timeline::new(false, 30.0),
+            timeline_bar: None,
+            frame: 0,
+            time_delta: 0.0,
             updated_time: None,
+            cursor_position: [0.0, 0.0],
+            mouse_pressed: false,
+            mouse: [0.0; 4],
+            modifiers: ModifiersState::empty(),
+            project,
+            export_settings,
+            cross_compile,
+            check_portability,
+            working_sources: None,
+            complexity: Vec::new(),
+            latest_sources: None,
+            previous_working_sources: None,
+            safety,
+            watchdog: None,
+            frames_since_reload: 0,
+            diagnostics,
+            error: None,
         }
     }
+
+    pub fn with_adapter_settings(mut self, adapter_settings: AdapterSettings) -> Self {
+        self.adapter_settings = adapter_settings;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    pub fn with_hud(mut self, visible: bool) -> Self {
+        self.hud_visible = visible;
+        self
+    }
+
+    pub fn with_version_stats(mut self, version_stats: VersionStats) -> Self {
+        self.version_stats = version_stats;
+        self
+    }
+
+    pub fn with_frame_pacing(mut self, frame_pacing: FramePacing) -> Self {
+        self.frame_pacing = frame_pacing;
+        self
+    }
 }
 
 impl App<'_> {
-    pub fn render(&mut self) {
-        let surface_texture = self.state.as_ref().unwrap().get_current_texture();
+    fn viewport_info(&self) -> ViewportInfo {
+        ViewportInfo {
+            size: self.viewport_size,
+            time_from_start_up: self.clock.from_start_up as f32,
+            time_from_update: self.clock.from_update as f32,
+            mouse: self.mouse,
+            frame: self.frame,
+            time_delta: self.time_delta,
+            _padding: [0; 2],
+        }
+    }
+
+    pub fn render(&mut self) -> Result<(), String> {
+        if let Some(reason) = self.state.as_ref().unwrap().lost_reason() {
+            return self.recover_device_lost(&reason);
+        }
+
+        let Some(surface_texture) = self.state.as_mut().unwrap().get_current_texture() else {
+            return Ok(());
+        };
         let surface_view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
@@ -49,39 +211,231 @@ impl App<'_> {
         let multi_sample_view =
             multi_sample_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
+        let (time_delta, frames) = self.clock.tick();
+        self.timeline.update(&mut self.clock);
+        self.time_delta = time_delta;
+        let viewport_info = self.viewport_info();
+
         let timer = std::time::Instant::now();
 
-        self.renderer.as_ref().unwrap().render(
+        // the passes and everything drawn over them go in one submit
+        let mut encoder = self.renderer.as_mut().unwrap().render(
             self.state.as_ref().unwrap().get_device(),
             self.state.as_ref().unwrap().get_queue(),
             &surface_view,
             &multi_sample_view,
-            crate::gpu::renderer::ViewportInfo {
-                size: self.viewport_size,
-                time_from_start_up: self.time_from_start_up.elapsed().as_secs_f32(),
-                time_from_update: self.time_from_update.elapsed().as_secs_f32(),
-            },
+            viewport_info,
+            self.gpu_timer.as_mut(),
         );
 
-        let render_time = timer.elapsed().as_micros();
+        if self.timeline.visible {
+            self.timeline_bar.as_ref().unwrap().render(
+                self.state.as_ref().unwrap().get_queue(),
+                &mut encoder,
+                &surface_view,
+                self.viewport_size,
+                &self.timeline.bar_state(self.clock.from_start_up),
+            );
+        }
+
+        if self.hud_visible {
+            self.render_hud(&mut encoder, &surface_view);
+        }
+
+        self.overlay.as_ref().unwrap().render(
+            self.state.as_ref().unwrap().get_queue(),
+            &mut encoder,
+            &surface_view,
+            self.viewport_size,
+        );
+
+        self.state
+            .as_ref()
+            .unwrap()
+            .get_queue()
+            .submit(std::iter::once(encoder.finish()));
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.submitted();
+        }
+
+        let cpu_time = timer.elapsed();
+        let render_time = cpu_time.as_micros();
+        self.frame = self.frame.saturating_add_signed(frames);
+
+        let pass_time = |pass_times: &[f64]| {
+            std::time::Duration::from_secs_f64(pass_times.iter().sum::<f64>() / 1000.0)
+        };
+        // measured for this frame, none where no new timestamps were read back
+        let fresh_gpu_time = if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            // the timestamps of a frame before, read back without waiting
+            self.state
+                .as_ref()
+                .unwrap()
+                .get_device()
+                .poll(wgpu::PollType::Poll)
+                .ok();
+            gpu_timer.take_pass_times().map(|pass_times| {
+                self.pass_times = pass_times;
+                pass_time(&self.pass_times)
+            })
+        } else if self.hud_visible {
+            // waiting for the gpu keeps the cpu from working ahead, so only while the time is shown
+            self.state
+                .as_ref()
+                .unwrap()
+                .get_device()
+                .poll(wgpu::PollType::Wait)
+                .ok();
+            Some(timer.elapsed() - cpu_time)
+        } else {
+            None
+        };
+        // the graph goes on with the latest readback
+        let gpu_time = fresh_gpu_time.or_else(|| {
+            (self.gpu_timer.is_some() && !self.pass_times.is_empty())
+                .then(|| pass_time(&self.pass_times))
+        });
+        self.frame_stats.record(cpu_time, gpu_time);
+        // each readback counts once
+        if fresh_gpu_time.is_some() {
+            if let Some(report) = self.version_stats.record(fresh_gpu_time) {
+                println!("\n{}", report);
+            }
+        }
+
+        if let Some(watchdog) = self.watchdog.as_ref() {
+            watchdog.submitted(self.state.as_ref().unwrap().get_queue());
+        }
 
         // print!("\r(updated: {:?})Render time: {:>6}μs", self.updated_time, render_time);
 
+        let mut status = self.clock.status();
+        if let Some(loop_status) = self.timeline.status() {
+            status.push_str(&format!(" {}", loop_status));
+        }
+        if let Some(typed_time) = self.typed_time.as_deref() {
+            status.push_str(&format!(" Jump to: {}s", typed_time));
+        }
+        // the gpu time where it is measured without waiting
+        let time_status = if self.gpu_timer.is_some() && !self.pass_times.is_empty() {
+            format!("GPU time:{:>7.3}ms", self.pass_times.iter().sum::<f64>())
+        } else {
+            format!("Render time:{:>5}μs", render_time)
+        };
         if let Some(updated_time) = self.updated_time.as_deref() {
             print!(
-                "\r(updated: {}) Render time:{:>5}μs",
-                updated_time, render_time
+                "\r(updated: {}) {} {}    ",
+                updated_time, time_status, status
             );
         } else {
-            print!("\rRender time:{:>5}μs", render_time);
+            print!("\r{} {}    ", time_status, status);
         }
         std::io::Write::flush(&mut std::io::stdout()).unwrap();
 
         surface_texture.present();
+        // a frame without a surface texture is tried again
+        self.frame_pacing.rendered();
+        self.frames_since_reload = self.frames_since_reload.saturating_add(1);
+
+        self.recover_uncaptured_errors();
+        Ok(())
+    }
+
+    // the error the event loop exited with
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    // nothing to go on with without a gpu
+    fn exit_with_error(&mut self, event_loop: &ActiveEventLoop, error: String) {
+        self.error = Some(error);
+        event_loop.exit();
+    }
+
+    // render the current frame into a float target and write it next to the shader file
+    pub fn export(&self) {
+        let state = self.state.as_ref().unwrap();
+        let size = [state.get_config().width, state.get_config().height];
+
+        let texture = self.renderer.as_ref().unwrap().render_float_target(
+            state.get_device(),
+            state.get_queue(),
+            size,
+            self.viewport_info(),
+        );
+        let pixels =
+            crate::gpu::readback::read_rgba32f(state.get_device(), state.get_queue(), &texture);
+
+        let path = self.export_settings.output_path(&self.project.image_path());
+        match crate::export::export_image(&path, size[0], size[1], &pixels, &self.export_settings) {
+            Ok(()) => println!("\nExported: {}", path.display()),
+            Err(e) => eprintln!("\nError when exporting: {}\n{}", path.display(), e),
+        }
+    }
+
+    // write the last working shader translated to the other shading languages next to it.
+    // for projects, this is the pass rendering to the window.
+    pub fn write_cross_compiled(&self) {
+        let Some(sources) = self.working_sources.as_deref() else {
+            return;
+        };
+
+        println!();
+        match self.project.parse_pass(self.project.image_index(), sources) {
+            Ok(shader) => crate::cross::print_written(&crate::cross::write_next_to(
+                &self.project.image_path(),
+                &shader,
+            )),
+            Err(e) => eprintln!("Error when cross compiling:\n{}", e),
+        }
+    }
+
+    // print the static complexity of every pass, with the changes from the last working code
+    fn print_complexity(&mut self, sources: &[String]) {
+        let complexity: Vec<_> = (0..self.project.manifest.passes.len())
+            .map(|index| {
+                let shader = self.project.parse_pass(index, sources).ok()?;
+                complexity::analyze(&shader.module, &shader.info)
+            })
+            .collect();
+
+        println!("Complexity:");
+        for (index, pass) in self.project.manifest.passes.iter().enumerate() {
+            let Some(current) = complexity[index].as_ref() else {
+                continue;
+            };
+            let previous = self
+                .complexity
+                .get(index)
+                .filter(|_| self.complexity.len() == complexity.len())
+                .and_then(Option::as_ref);
+            println!(
+                "  {:<11} {}",
+                pass.name,
+                complexity::summary(current, previous)
+            );
+        }
+        println!();
+        self.complexity = complexity;
+    }
+
+    // check the last working shader against the other platforms wgpu runs on
+    pub fn print_portability(&self) {
+        let Some(sources) = self.working_sources.as_deref() else {
+            return;
+        };
+
+        println!();
+        match self.project.parse_pass(self.project.image_index(), sources) {
+            Ok(shader) => {
+                crate::portability::print_report(&crate::portability::check_all(&shader.module))
+            }
+            Err(e) => eprintln!("Error when checking portability:\n{}", e),
+        }
     }
 }
 
-impl ApplicationHandler<(Option<SystemTime>, String)> for App<'_> {
+impl ApplicationHandler<(Option<SystemTime>, Vec<String>)> for App<'_> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         self.window = Some(Arc::new(
             event_loop
@@ -94,24 +448,28 @@ impl ApplicationHandler<(Option<SystemTime>, String)> for App<'_> {
 
         // self.window.as_ref().unwrap().set_decorations(false);
 
-        // make gpu state
-        self.state = Some(pollster::block_on(GpuState::new(
-            self.window.as_ref().unwrap().clone(),
-        )));
-
-        // prepare renderer
-        self.renderer = Some(Renderer::new(
-            self.state.as_ref().unwrap().get_device(),
-            self.state.as_ref().unwrap().get_queue(),
-            self.state.as_ref().unwrap().get_surface_format(),
-            self.default_fragment_code,
-        ));
+        if let Err(e) = self.create_gpu_state() {
+            self.exit_with_error(event_loop, e);
+            return;
+        }
 
         // get the viewport size
         self.viewport_size = self.window.as_ref().unwrap().inner_size().into();
     }
 
-    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        // without a gpu the event loop is exiting
+        if self.state.is_none() {
+            return;
+        }
+        // moving the cursor only changes the frame while dragging
+        if !matches!(event, WindowEvent::CursorMoved { .. })
+            || self.mouse_pressed
+            || self.timeline.scrubbing
+        {
+            self.frame_pacing.request_redraw();
+        }
+
         let mut redraw = false;
         match event {
             WindowEvent::CloseRequested => {
@@ -122,46 +480,454 @@ impl ApplicationHandler<(Option<SystemTime>, String)> for App<'_> {
                 redraw = true;
             }
             WindowEvent::Resized(new_size) => {
+                self.frame_pacing
+                    .set_minimized(new_size.width == 0 || new_size.height == 0);
                 self.state.as_mut().unwrap().resize(new_size);
                 self.viewport_size = new_size.into();
                 redraw = true;
             }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = [position.x as f32, position.y as f32];
+                if self.timeline.scrubbing {
+                    self.scrub();
+                } else if self.mouse_pressed {
+                    self.mouse[0] = self.cursor_position[0];
+                    self.mouse[1] = self.cursor_position[1];
+                }
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } if self.timeline.scrubbing
+                || (state == ElementState::Pressed
+                    && self
+                        .timeline
+                        .contains(self.cursor_position, self.viewport_size)) =>
+            {
+                // the bar takes the mouse from the shader
+                self.timeline.scrubbing = state == ElementState::Pressed;
+                if self.timeline.scrubbing {
+                    self.scrub();
+                }
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.mouse_pressed = state == ElementState::Pressed;
+                if self.mouse_pressed {
+                    let [x, y] = self.cursor_position;
+                    self.mouse = [x, y, x, y];
+                } else {
+                    self.mouse[2] = -self.mouse[2].abs();
+                    self.mouse[3] = -self.mouse[3].abs();
+                }
+            }
+            WindowEvent::Focused(focused) => self.frame_pacing.set_focused(focused),
+            WindowEvent::Occluded(occluded) => self.frame_pacing.set_occluded(occluded),
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+            }
+            // stepping repeats while the key is held
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state == ElementState::Pressed
+                    && matches!(
+                        event.physical_key,
+                        PhysicalKey::Code(KeyCode::ArrowRight | KeyCode::ArrowLeft)
+                    ) =>
+            {
+                let steps = if self.modifiers.shift_key() { 10 } else { 1 };
+                if event.physical_key == PhysicalKey::Code(KeyCode::ArrowRight) {
+                    self.clock.step_by(steps);
+                } else {
+                    self.clock.step_by(-steps);
+                }
+            }
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state == ElementState::Pressed && !event.repeat =>
+            {
+                if let PhysicalKey::Code(code) = event.physical_key {
+                    self.time_key(code);
+                }
+                match event.physical_key {
+                    PhysicalKey::Code(KeyCode::F12) => self.export(),
+                    PhysicalKey::Code(KeyCode::KeyV) => {
+                        println!("\n\n{}", self.version_stats.history());
+                    }
+                    PhysicalKey::Code(KeyCode::KeyC) => {
+                        self.cross_compile = !self.cross_compile;
+                        println!(
+                            "\nCross compile on reload: {}",
+                            if self.cross_compile { "on" } else { "off" }
+                        );
+                        if self.cross_compile {
+                            self.write_cross_compiled();
+                        }
+                    }
+                    _ => {}
+                }
+            }
             _ => {}
         }
 
         if redraw {
-            self.render();
+            if let Err(e) = self.render() {
+                self.exit_with_error(event_loop, e);
+            }
+        }
+    }
+
+    // render when the frame pacing says so, and wait for the next frame or event
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_none() {
+            return;
+        }
+        let due = self
+            .frame_pacing
+            .next_frame(self.animated)
+            .is_some_and(|time| time <= Instant::now());
+        if due {
+            if let Err(e) = self.render() {
+                self.exit_with_error(event_loop, e);
+                return;
+            }
+        }
+        event_loop.set_control_flow(self.frame_pacing.control_flow(self.animated));
+    }
+
+    fn user_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        event: (Option<SystemTime>, Vec<String>),
+    ) {
+        if self.state.is_none() {
+            return;
+        }
+        // event is the new code of every file of the project
+        let (update_time, sources) = event;
+        self.updated_time =
+            update_time.map(|update_time| format_utc_to_string(&update_time.into()));
+
+        self.reload(sources);
+    }
+}
+
+impl App<'_> {
+    // pause, speed, reset and jumps of the clocks, and the timeline
+    fn time_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Space => self.clock.toggle_pause(),
+            KeyCode::ArrowUp => self.clock.set_speed(self.clock.speed * 2.0),
+            KeyCode::ArrowDown => self.clock.set_speed(self.clock.speed / 2.0),
+            KeyCode::KeyB => self.clock.set_speed(-self.clock.speed),
+            KeyCode::KeyN => self.clock.set_speed(1.0),
+            KeyCode::KeyR if self.modifiers.shift_key() => self.clock.reset_update(),
+            KeyCode::KeyR => self.clock.reset_start_up(),
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                if let Some(time) = self.typed_time.take().and_then(|time| time.parse().ok()) {
+                    self.clock.jump_to(time);
+                }
+            }
+            KeyCode::Escape => self.typed_time = None,
+            KeyCode::KeyT => self.timeline.visible = !self.timeline.visible,
+            KeyCode::KeyH => self.hud_visible = !self.hud_visible,
+            KeyCode::KeyI => self.timeline.loop_in = Some(self.clock.from_start_up),
+            KeyCode::KeyO => self.timeline.loop_out = Some(self.clock.from_start_up),
+            KeyCode::KeyL => {
+                self.timeline.loop_in = None;
+                self.timeline.loop_out = None;
+            }
+            KeyCode::KeyM => self.timeline.toggle_bookmark(self.clock.from_start_up),
+            KeyCode::BracketLeft => {
+                if let Some(time) = self.timeline.previous_bookmark(self.clock.from_start_up) {
+                    self.clock.jump_to(time);
+                }
+            }
+            KeyCode::BracketRight => {
+                if let Some(time) = self.timeline.next_bookmark(self.clock.from_start_up) {
+                    self.clock.jump_to(time);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(typed_time) = self.typed_time.as_mut() {
+                    typed_time.pop();
+                    if typed_time.is_empty() {
+                        self.typed_time = None;
+                    }
+                }
+            }
+            _ => {
+                if let Some(character) = typed_character(code) {
+                    self.typed_time
+                        .get_or_insert_with(String::new)
+                        .push(character);
+                }
+            }
+        }
+    }
+
+    // the numbers are written a few times a second, so they can be read
+    fn render_hud(&mut self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let state = self.state.as_ref().unwrap();
+        let hud = self.hud.as_mut().unwrap();
+
+        if self
+            .hud_updated
+            .is_none_or(|updated| updated.elapsed().as_millis() >= 250)
+        {
+            let config = state.get_config();
+            // named like in the project, if the passes rendered are the ones of the project
+            let passes = &self.project.manifest.passes;
+            let pass_times = self.gpu_timer.as_ref().map(|_| {
+                self.pass_times
+                    .iter()
+                    .enumerate()
+                    .map(|(index, time)| {
+                        let name = passes
+                            .get(index)
+                            .filter(|_| passes.len() == self.pass_times.len())
+                            .map_or("?", |pass| pass.name.as_str());
+                        (name, *time)
+                    })
+                    .collect::<Vec<_>>()
+            });
+            let mut text = self.frame_stats.text(
+                [config.width, config.height],
+                &state.get_adapter_info(),
+                pass_times.as_deref(),
+            );
+            if let Some(change) = self.version_stats.change() {
+                text.push_str(&format!("\nVersion {:>+7.0}% GPU", change));
+            }
+            hud.set_text(state.get_device(), state.get_queue(), &text);
+            self.hud_updated = Some(std::time::Instant::now());
+        }
+
+        hud.render(
+            state.get_queue(),
+            encoder,
+            view,
+            self.viewport_size,
+            &self.frame_stats.graph(),
+        );
+    }
+
+    // move the time to where the cursor is on the timeline
+    fn scrub(&mut self) {
+        let time = self
+            .timeline
+            .time_at(self.cursor_position[0], self.viewport_size[0]);
+        self.clock.jump_to(time);
+    }
+
+    // the device, the renderer and everything else living on the gpu
+    fn create_gpu_state(&mut self) -> Result<(), String> {
+        // the old device goes first, the window can only have one surface
+        self.watchdog = None;
+        self.gpu_timer = None;
+        self.timeline_bar = None;
+        self.hud = None;
+        self.overlay = None;
+        self.renderer = None;
+        self.state = None;
+
+        // make gpu state
+        self.state = Some(pollster::block_on(GpuState::new(
+            self.window.as_ref().unwrap().clone(),
+            &self.adapter_settings,
+            self.frame_pacing.present_mode.into(),
+        ))?);
+        // the default shader runs until code compiles
+        self.animated = true;
+
+        // prepare renderer
+        let gpu = crate::gpu::adapter::describe(&self.state.as_ref().unwrap().get_adapter_info());
+        if self.state.as_ref().unwrap().get_sample_count() == 1 {
+            println!("GPU: {}, without MSAA", gpu);
+        } else {
+            println!("GPU: {}", gpu);
+        }
+        self.diagnostics.set_gpu(gpu);
+
+        self.renderer = Some(Renderer::new(
+            self.state.as_ref().unwrap().get_device(),
+            self.state.as_ref().unwrap().get_queue(),
+            self.state.as_ref().unwrap().get_surface_format(),
+            self.state.as_ref().unwrap().get_sample_count(),
+            self.default_fragment_code,
+        ));
+
+        self.overlay = Some(Overlay::new(
+            self.state.as_ref().unwrap().get_device(),
+            self.state.as_ref().unwrap().get_surface_format(),
+        ));
+
+        self.hud = Some(Hud::new(
+            self.state.as_ref().unwrap().get_device(),
+            self.state.as_ref().unwrap().get_surface_format(),
+        ));
+        self.hud_updated = None;
+
+        self.gpu_timer = GpuTimer::new(
+            self.state.as_ref().unwrap().get_device(),
+            self.state.as_ref().unwrap().get_queue(),
+        );
+        self.pass_times.clear();
+        if self.gpu_timer.is_none() {
+            eprintln!(
+                "The GPU does not support timestamp queries, the GPU time is measured by waiting for the GPU while the HUD is shown"
+            );
         }
+
+        self.timeline_bar = Some(TimelineBar::new(
+            self.state.as_ref().unwrap().get_device(),
+            self.state.as_ref().unwrap().get_surface_format(),
+        ));
+
+        self.watchdog = self.safety.map(|safety| {
+            Watchdog::new(
+                self.state.as_ref().unwrap().get_device().clone(),
+                safety.watchdog_timeout,
+            )
+        });
+
+        Ok(())
     }
 
-    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: winit::event::StartCause) {
-        if cause == winit::event::StartCause::Poll {
-            self.render();
+    // start over on a new device with the latest code.
+    // if the watchdog destroyed the device, or it was lost right after a reload, with the code working before.
+    fn recover_device_lost(&mut self, reason: &str) -> Result<(), String> {
+        let hung = self.watchdog.as_ref().is_some_and(Watchdog::is_triggered);
+        // reloading the code losing the device would lose the next one too
+        let reloaded = self.working_sources.is_some() && self.frames_since_reload < RELOAD_FRAMES;
+        if !hung {
+            eprintln!(
+                "\n\nThe GPU device was lost, creating a new one.\n{}",
+                reason
+            );
+        }
+
+        self.create_gpu_state()?;
+
+        if !hung && !reloaded {
+            if let Some(sources) = self.latest_sources.take() {
+                self.reload(sources);
+            }
+            return Ok(());
         }
+
+        self.working_sources = None;
+        if let Some(sources) = self.previous_working_sources.take() {
+            self.compile(sources);
+        }
+        let error = if hung {
+            format!(
+                "A frame took longer than {} ms, the shader is reverted to the last working one\n\
+                 it may loop forever, or be too slow even with `--loop-limit` iterations per loop entry",
+                self.safety.unwrap().watchdog_timeout.as_millis()
+            )
+        } else {
+            "The GPU device was lost right after the reload, the shader is reverted to the last working one"
+                .to_string()
+        };
+        self.diagnostics.error(&self.project.image_path(), &error);
+        self.update_overlay();
+        Ok(())
     }
 
-    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: (Option<SystemTime>, String)) {
-        // event is the new fragment code
+    // errors of the passes on screen, which passed validation when they were created.
+    // the last passes which rendered without error are put back.
+    fn recover_uncaptured_errors(&mut self) {
+        let errors = self.state.as_ref().unwrap().take_uncaptured_errors();
+        let Some(error) = errors.first() else {
+            return;
+        };
+
+        self.diagnostics.begin(self.updated_time.as_deref());
+        self.diagnostics
+            .gpu_error(&self.project.image_path(), error);
+
+        let state = self.state.as_ref().unwrap();
+        if !self.renderer.as_mut().unwrap().revert() {
+            // nothing rendered yet, start with the default shader
+            self.renderer = Some(Renderer::new(
+                state.get_device(),
+                state.get_queue(),
+                state.get_surface_format(),
+                state.get_sample_count(),
+                self.default_fragment_code,
+            ));
+        }
+        self.working_sources = None;
+        self.animated = true;
+
+        self.update_overlay();
+    }
 
+    // show the errors in the window until the next successful compile
+    fn update_overlay(&mut self) {
+        let state = self.state.as_ref().unwrap();
+        self.overlay.as_mut().unwrap().set_text(
+            state.get_device(),
+            state.get_queue(),
+            self.diagnostics.overlay_text().as_deref(),
+        );
+    }
+
+    fn reload(&mut self, sources: Vec<String>) {
+        self.compile(sources);
+        self.update_overlay();
+        self.frame_pacing.request_redraw();
+    }
+
+    fn compile(&mut self, sources: Vec<String>) {
         // update the fragment code and pipeline
 
-        let (update_time, fragment_code) = event;
-        self.updated_time =
-            update_time.map(|update_time| format_utc_to_string(&update_time.into()));
+        self.latest_sources = Some(sources.clone());
+        self.diagnostics.begin(self.updated_time.as_deref());
 
-        if let Err(e) = pollster::block_on(
+        let f_shader_sources = match self
+            .project
+            .shader_sources(&sources, self.safety.map(|safety| safety.loop_limit))
+        {
+            Ok(f_shader_sources) => f_shader_sources,
+            Err((index, e)) => {
+                self.diagnostics
+                    .shader_error(&self.project.pass_path(index), &sources[index], &e);
+                return;
+            }
+        };
+
+        let state = self.state.as_ref().unwrap();
+        if let Err((index, e)) =
+            crate::features::check_passes(&f_shader_sources, state.get_device().features())
+        {
+            self.diagnostics.error(&self.project.pass_path(index), &e);
+            return;
+        }
+        let passes =
+            self.project
+                .pass_descriptors(state.get_device(), state.get_queue(), f_shader_sources);
+
+        if let Err((index, e)) = pollster::block_on(
             self.renderer
                 .as_mut()
                 .unwrap()
-                .update_fragment(&fragment_code, self.state.as_ref().unwrap().get_device()),
+                .update_fragment(passes, state.get_device()),
         ) {
-            eprintln!("Error:\n{}", e);
+            self.diagnostics
+                .gpu_error(&self.project.pass_path(index), &e);
             return;
         }
 
         // try to render
 
-        let surface_texture = self.state.as_ref().unwrap().get_current_texture();
+        let Some(surface_texture) = self.state.as_mut().unwrap().get_current_texture() else {
+            eprintln!("\nNo surface to render to, the new shader is not used");
+            return;
+        };
         let surface_view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
@@ -170,23 +936,73 @@ impl ApplicationHandler<(Option<SystemTime>, String)> for App<'_> {
         let multi_sample_view =
             multi_sample_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
+        self.frame = 0;
+        self.clock.restart_tick();
+        self.frame_stats.restart();
+        let viewport_info = self.viewport_info();
+
         if let Err(e) = pollster::block_on(self.renderer.as_mut().unwrap().try_render(
             self.state.as_ref().unwrap().get_device(),
             self.state.as_ref().unwrap().get_queue(),
             &surface_view,
             &multi_sample_view,
-            crate::gpu::renderer::ViewportInfo {
-                size: self.viewport_size,
-                time_from_start_up: self.time_from_start_up.elapsed().as_secs_f32(),
-                time_from_update: self.time_from_update.elapsed().as_secs_f32(),
-            },
+            viewport_info,
         )) {
-            eprintln!("Error:\n{}", e);
+            self.diagnostics.gpu_error(&self.project.image_path(), &e);
             return;
         }
 
+        if let Some(watchdog) = self.watchdog.as_ref() {
+            watchdog.submitted(self.state.as_ref().unwrap().get_queue());
+        }
+
         surface_texture.present();
-        self.time_from_update = std::time::Instant::now();
+        self.clock.reset_update();
+        self.frame += 1;
+
+        // lints do not stop the new code, they are reported with it
+        for index in 0..self.project.manifest.passes.len() {
+            let lints = self.project.lint_pass(index, &sources);
+            self.diagnostics
+                .warnings(&self.project.pass_path(index), &sources[index], &lints);
+        }
+        self.diagnostics
+            .success(&self.project.manifest.name, self.updated_time.as_deref());
+        self.version_stats
+            .begin_version(self.updated_time.as_deref());
+        // the timestamps of frames of the code before are not put on the new one
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.restart();
+        }
+        self.pass_times.clear();
+        self.print_complexity(&sources);
+        self.animated = frame_pacing::is_animated(&self.project, &sources);
+        self.frames_since_reload = 0;
+
+        self.previous_working_sources = self.working_sources.replace(sources);
+        if self.cross_compile {
+            self.write_cross_compiled();
+        }
+        if self.check_portability {
+            self.print_portability();
+        }
+    }
+}
+
+fn typed_character(code: KeyCode) -> Option<char> {
+    match code {
+        KeyCode::Digit0 | KeyCode::Numpad0 => Some('0'),
+        KeyCode::Digit1 | KeyCode::Numpad1 => Some('1'),
+        KeyCode::Digit2 | KeyCode::Numpad2 => Some('2'),
+        KeyCode::Digit3 | KeyCode::Numpad3 => Some('3'),
+        KeyCode::Digit4 | KeyCode::Numpad4 => Some('4'),
+        KeyCode::Digit5 | KeyCode::Numpad5 => Some('5'),
+        KeyCode::Digit6 | KeyCode::Numpad6 => Some('6'),
+        KeyCode::Digit7 | KeyCode::Numpad7 => Some('7'),
+        KeyCode::Digit8 | KeyCode::Numpad8 => Some('8'),
+        KeyCode::Digit9 | KeyCode::Numpad9 => Some('9'),
+        KeyCode::Period | KeyCode::NumpadDecimal => Some('.'),
+        _ => None,
     }
 }
 